@@ -0,0 +1,158 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::UnorderedMap;
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{account_hash, AccountIdWrapper, ContractError, StorageKey, TodoEvent, TodoListV1, TodoListV1Ext};
+
+/// Scopes an account owner can hand to another account through `grant_access`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Permission {
+    ManageTasks,
+    CompleteTasks,
+    ManageRewards,
+    RedeemRewards,
+    ManageSchedule,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct AccessGrant {
+    delegate: AccountIdWrapper,
+    permissions: Vec<Permission>,
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Lets `account_id` act on the caller's data within `permissions`,
+    /// replacing any scopes granted to it before.
+    #[handle_result]
+    pub fn grant_access(&mut self, account_id: AccountId, permissions: Vec<Permission>) -> Result<(), ContractError> {
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = self.begin_storage_tracking(&owner_id)?;
+        if account_id == owner_id {
+            return Err(ContractError::Unauthorized);
+        }
+        let mut permissions = permissions;
+        permissions.sort();
+        permissions.dedup();
+
+        TodoEvent::AccessGranted { account_id: &owner_id, delegate: &account_id, permissions: &permissions }.emit();
+        let grants = self.access_grants.entry(owner_id.clone()).or_insert_with(|| {
+            UnorderedMap::new(StorageKey::AccountAccessGrants { account_hash: account_hash(&owner_id) })
+        });
+        grants.insert(account_id, permissions);
+        self.settle_storage(&owner_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    #[handle_result]
+    pub fn revoke_access(&mut self, account_id: AccountId) -> Result<(), ContractError> {
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = self.begin_storage_tracking(&owner_id)?;
+
+        self.access_grants
+            .get_mut(&owner_id)
+            .and_then(|grants| grants.remove(&account_id))
+            .ok_or_else(|| ContractError::GrantNotFound(account_id.clone()))?;
+        TodoEvent::AccessRevoked { account_id: &owner_id, delegate: &account_id }.emit();
+        self.settle_storage(&owner_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    pub fn get_access_grants(&self, account_id: AccountId) -> Vec<AccessGrant> {
+        self.access_grants
+            .get(&account_id)
+            .map(|grants| {
+                grants
+                    .iter()
+                    .map(|(delegate, permissions)| AccessGrant {
+                        delegate: AccountIdWrapper(delegate.clone()),
+                        permissions: permissions.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn has_access(&self, account_id: AccountId, delegate: AccountId, permission: Permission) -> bool {
+        account_id == delegate || self.has_permission(&account_id, &delegate, permission)
+    }
+}
+
+impl TodoListV1 {
+    /// Resolves the account a call operates on. Without `account_id` that is the
+    /// predecessor itself; otherwise the predecessor needs `permission` from it,
+    /// either as a delegate or through their role in a workspace.
+    pub(crate) fn authorize(&self, account_id: Option<AccountId>, permission: Permission) -> Result<AccountId, ContractError> {
+        let caller = env::predecessor_account_id();
+        match account_id {
+            None => Ok(caller),
+            Some(owner_id) if owner_id == caller || self.has_permission(&owner_id, &caller, permission) => Ok(owner_id),
+            Some(_) => Err(ContractError::Unauthorized),
+        }
+    }
+
+    fn has_permission(&self, owner_id: &AccountId, delegate: &AccountId, permission: Permission) -> bool {
+        self.has_workspace_permission(owner_id, delegate, permission)
+            || self
+                .access_grants
+                .get(owner_id)
+                .and_then(|grants| grants.get(delegate))
+                .is_some_and(|permissions| permissions.contains(&permission))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    #[test]
+    fn test_delegate_manages_tasks_within_scope() {
+        let mut contract = setup_contract(accounts(1));
+        contract.grant_access(accounts(2), vec![Permission::ManageTasks]).unwrap();
+
+        testing_env!(get_context(accounts(2)).build());
+        let task_id = contract
+            .add_task("Delegated".to_string(), "".to_string(), 1, None, None, 10, Some(accounts(1)))
+            .unwrap();
+        assert_eq!(contract.get_tasks(accounts(1)).len(), 1);
+        assert_eq!(contract.mark_complete(task_id, Some(accounts(1))), Err(ContractError::Unauthorized));
+        assert_eq!(contract.redeem_reward(1, Some(accounts(1))), Err(ContractError::Unauthorized));
+    }
+
+    #[test]
+    fn test_revoke_access() {
+        let mut contract = setup_contract(accounts(1));
+        contract.grant_access(accounts(2), vec![Permission::ManageTasks, Permission::CompleteTasks]).unwrap();
+        assert!(contract.has_access(accounts(1), accounts(2), Permission::CompleteTasks));
+
+        contract.revoke_access(accounts(2)).unwrap();
+        assert!(!contract.has_access(accounts(1), accounts(2), Permission::CompleteTasks));
+        assert!(contract.get_access_grants(accounts(1)).is_empty());
+        assert_eq!(contract.revoke_access(accounts(2)), Err(ContractError::GrantNotFound(accounts(2))));
+
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(
+            contract.add_task("Delegated".to_string(), "".to_string(), 1, None, None, 10, Some(accounts(1))),
+            Err(ContractError::Unauthorized)
+        );
+    }
+
+    #[test]
+    fn test_calls_through_another_contract_act_on_predecessor() {
+        let mut contract = setup_contract(accounts(1));
+
+        let mut context = get_context(accounts(2));
+        context.signer_account_id(accounts(1));
+        testing_env!(context.build());
+        assert_eq!(
+            contract.add_task("Injected".to_string(), "".to_string(), 1, None, None, 10, None),
+            Err(ContractError::NotRegistered(accounts(2)))
+        );
+        assert!(contract.get_tasks(accounts(1)).is_empty());
+    }
+}
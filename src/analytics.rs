@@ -0,0 +1,268 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+
+use crate::time::{civil_from_days, days_from_civil, local_day_start, weekday_index};
+use crate::{ContractError, DurationMinutes, Permission, PointsChange, TodoEvent, TodoListV1, TodoListV1Ext};
+
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug, Default)]
+pub struct Streaks {
+    /// Consecutive days with a completion, ending today, or yesterday while
+    /// nothing has been completed today yet.
+    current: u32,
+    longest: u32,
+}
+
+/// Completions and points within one week or month.
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug)]
+pub struct PeriodStats {
+    /// First local day of the period, counted from the Unix epoch. Weeks
+    /// start on Monday.
+    start_day: u32,
+    completed: u32,
+    points_earned: i64,
+}
+
+/// Statistics over a range of days. Counts and points include tasks removed
+/// since; the deadline and time figures only cover tasks still in the list.
+/// Days rolled up into monthly totals are left out.
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct ProductivityStats {
+    completed: u32,
+    /// Points credited for completions, less those reversed by reopening.
+    points_earned: i64,
+    completed_with_deadline: u32,
+    completed_on_time: u32,
+    on_time_percent: Option<u32>,
+    /// Mean time between completion and deadline, in nanoseconds. Negative
+    /// when tasks are completed late on average.
+    average_lead_time: Option<i64>,
+    /// Tasks with both an estimate and logged actual time, which the two
+    /// totals below cover.
+    timed_tasks: u32,
+    estimated_time: DurationMinutes,
+    actual_time: DurationMinutes,
+    weeks: Vec<PeriodStats>,
+    months: Vec<PeriodStats>,
+}
+
+fn week_start(day: u32) -> u32 {
+    day.saturating_sub(weekday_index(u64::from(day)) as u32)
+}
+
+fn month_start(day: u32) -> u32 {
+    let (year, month, _) = civil_from_days(u64::from(day));
+    days_from_civil(year, month, 1) as u32
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Records how long the task actually took, for comparison with its
+    /// estimate in `get_productivity_stats`.
+    #[handle_result]
+    pub fn set_actual_time(&mut self, id: u64, actual_time: Option<DurationMinutes>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::CompleteTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        if let Some(duration) = actual_time.filter(|duration| duration.minutes() == 0) {
+            return Err(ContractError::InvalidDuration(duration));
+        }
+
+        let task = self.task_mut(&account_id, id)?;
+        task.actual_time = actual_time;
+        TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    pub fn get_streaks(&self, account_id: AccountId) -> Streaks {
+        let mut days = self.completion_days(&account_id, 0, u32::MAX);
+        days.sort_unstable_by_key(|(day, _)| *day);
+        let mut streaks = Streaks::default();
+        let mut run = 0;
+        let mut last_day = None;
+        for (day, _) in days {
+            run = if last_day.is_some_and(|last_day| last_day + 1 == day) { run + 1 } else { 1 };
+            streaks.longest = streaks.longest.max(run);
+            last_day = Some(day);
+        }
+        let today = self.today(&account_id);
+        if last_day.is_some_and(|day| day + 1 >= today) {
+            streaks.current = run;
+        }
+        streaks
+    }
+
+    /// Statistics for tasks completed from `from_day` through `to_day`, both
+    /// local days counted from the Unix epoch.
+    pub fn get_productivity_stats(&self, account_id: AccountId, from_day: u32, to_day: u32) -> ProductivityStats {
+        let mut stats = ProductivityStats {
+            completed: 0,
+            points_earned: 0,
+            completed_with_deadline: 0,
+            completed_on_time: 0,
+            on_time_percent: None,
+            average_lead_time: None,
+            timed_tasks: 0,
+            estimated_time: DurationMinutes::from_minutes(0),
+            actual_time: DurationMinutes::from_minutes(0),
+            weeks: vec![],
+            months: vec![],
+        };
+        let mut weeks = BTreeMap::new();
+        let mut months = BTreeMap::new();
+        let mut add_to_periods = |day: u32, completed: u32, points: i64| {
+            for (periods, start_day) in [(&mut weeks, week_start(day)), (&mut months, month_start(day))] {
+                let period = periods.entry(start_day).or_insert(PeriodStats { start_day, completed: 0, points_earned: 0 });
+                period.completed += completed;
+                period.points_earned += points;
+            }
+        };
+
+        for (day, count) in self.completion_days(&account_id, from_day, to_day) {
+            stats.completed += count;
+            add_to_periods(day, count, 0);
+        }
+        for (timestamp, amount) in self.task_points_between(&account_id, from_day, to_day) {
+            stats.points_earned += amount;
+            add_to_periods(self.local_day(&account_id, timestamp), 0, amount);
+        }
+
+        let mut total_lead_time: i128 = 0;
+        let (mut estimated, mut actual) = (0u32, 0u32);
+        let completed_tasks = self.tasks.get(&account_id).into_iter().flat_map(|tasks| tasks.values()).filter_map(|task| {
+            let completed_at = task.completed_at?;
+            (from_day..=to_day).contains(&task.completed_day?).then_some((task, completed_at))
+        });
+        for (task, completed_at) in completed_tasks {
+            if let Some(deadline) = task.deadline {
+                stats.completed_with_deadline += 1;
+                stats.completed_on_time += u32::from(completed_at <= deadline);
+                total_lead_time += i128::from(deadline) - i128::from(completed_at);
+            }
+            if let (Some(estimate), Some(actual_time)) = (task.estimated_time, task.actual_time) {
+                stats.timed_tasks += 1;
+                estimated = estimated.saturating_add(estimate.minutes());
+                actual = actual.saturating_add(actual_time.minutes());
+            }
+        }
+        stats.on_time_percent = (stats.completed_on_time * 100).checked_div(stats.completed_with_deadline);
+        stats.average_lead_time = total_lead_time.checked_div(i128::from(stats.completed_with_deadline)).map(|lead_time| lead_time as i64);
+        stats.estimated_time = DurationMinutes::from_minutes(estimated);
+        stats.actual_time = DurationMinutes::from_minutes(actual);
+        stats.weeks = weeks.into_values().collect();
+        stats.months = months.into_values().collect();
+        stats
+    }
+}
+
+impl TodoListV1 {
+    /// Days from `from_day` through `to_day` with at least one completion,
+    /// in no particular order.
+    fn completion_days(&self, account_id: &AccountId, from_day: u32, to_day: u32) -> Vec<(u32, u32)> {
+        self.completed_tasks_per_day
+            .get(account_id)
+            .map(|days| days.iter().filter(|(day, _)| (from_day..=to_day).contains(*day)).map(|(day, count)| (*day, *count)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Timestamp and amount of the ledger entries for completed and reopened
+    /// tasks within the days.
+    fn task_points_between(&self, account_id: &AccountId, from_day: u32, to_day: u32) -> Vec<(u64, i64)> {
+        let Some(ledger) = self.points_ledger.get(account_id) else {
+            return vec![];
+        };
+        let utc_offset = self.get_utc_offset(account_id.clone());
+        let from = local_day_start(from_day, utc_offset);
+        let until = to_day.checked_add(1).map_or(u64::MAX, |day| local_day_start(day, utc_offset));
+        // Entries are appended in time order, so the range starts at the
+        // first entry not before `from`.
+        let (mut low, mut high) = (0, ledger.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if ledger.get(middle).is_some_and(|entry| entry.timestamp < from) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        (low..ledger.len())
+            .filter_map(|index| ledger.get(index))
+            .take_while(|entry| entry.timestamp < until)
+            .filter(|entry| matches!(entry.change, PointsChange::TaskCompleted { .. } | PointsChange::TaskReopened { .. }))
+            .map(|entry| (entry.timestamp, entry.amount))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use crate::time::NANOS_PER_DAY;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    // 2022-01-03, a Monday.
+    const MONDAY: u32 = 18_995;
+
+    fn start_of(day: u32) -> u64 {
+        u64::from(day) * NANOS_PER_DAY
+    }
+
+    fn complete_on(contract: &mut TodoListV1, day: u32, deadline_day: Option<u32>) -> u64 {
+        testing_env!(get_context(accounts(1)).block_timestamp(start_of(day) + 1).build());
+        let deadline = deadline_day.map(start_of);
+        let id = contract.add_task("Task".to_string(), "".to_string(), 1, deadline, None, 10, None).unwrap();
+        contract.mark_complete(id, None).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_streaks() {
+        let mut contract = setup_contract(accounts(1));
+        for day in [MONDAY, MONDAY + 1, MONDAY + 2, MONDAY + 5, MONDAY + 6] {
+            complete_on(&mut contract, day, None);
+        }
+        testing_env!(get_context(accounts(1)).block_timestamp(start_of(MONDAY + 7)).build());
+        assert_eq!(contract.get_streaks(accounts(1)), Streaks { current: 2, longest: 3 });
+        testing_env!(get_context(accounts(1)).block_timestamp(start_of(MONDAY + 8)).build());
+        assert_eq!(contract.get_streaks(accounts(1)), Streaks { current: 0, longest: 3 });
+    }
+
+    #[test]
+    fn test_productivity_stats() {
+        let mut contract = setup_contract(accounts(1));
+        let early = complete_on(&mut contract, MONDAY, Some(MONDAY + 2));
+        complete_on(&mut contract, MONDAY + 1, Some(MONDAY));
+        complete_on(&mut contract, MONDAY + 7, None);
+        complete_on(&mut contract, MONDAY + 30, None);
+        contract.update_task(early, "Task".to_string(), "".to_string(), 1, Some(start_of(MONDAY + 2)), Some(DurationMinutes::from_hm(1, 0)), 10, None).unwrap();
+        contract.set_actual_time(early, Some(DurationMinutes::from_hm(1, 30)), None).unwrap();
+
+        let stats = contract.get_productivity_stats(accounts(1), MONDAY, MONDAY + 13);
+        assert_eq!(stats.completed, 3);
+        assert_eq!(stats.points_earned, 30);
+        assert_eq!((stats.completed_with_deadline, stats.completed_on_time, stats.on_time_percent), (2, 1, Some(50)));
+        // Two days early and one day late.
+        assert_eq!(stats.average_lead_time, Some((NANOS_PER_DAY / 2) as i64 - 1));
+        assert_eq!((stats.timed_tasks, stats.estimated_time, stats.actual_time), (1, DurationMinutes::from_hm(1, 0), DurationMinutes::from_hm(1, 30)));
+        assert_eq!(
+            stats.weeks,
+            vec![
+                PeriodStats { start_day: MONDAY, completed: 2, points_earned: 20 },
+                PeriodStats { start_day: MONDAY + 7, completed: 1, points_earned: 10 },
+            ]
+        );
+        assert_eq!(stats.months, vec![PeriodStats { start_day: MONDAY - 2, completed: 3, points_earned: 30 }]);
+        assert_eq!(contract.get_productivity_stats(accounts(1), MONDAY + 30, MONDAY + 30).months[0].start_day, MONDAY + 29);
+    }
+
+    #[test]
+    fn test_first_week_starts_at_epoch() {
+        // 1970-01-01 was a Thursday.
+        assert_eq!(week_start(2), 0);
+        assert_eq!(week_start(4), 4);
+        assert_eq!(week_start(MONDAY + 6), MONDAY);
+    }
+}
@@ -0,0 +1,338 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::UnorderedMap;
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::time::NANOS_PER_DAY;
+use crate::{account_hash, AccountIdWrapper, ContractError, Permission, PointsChange, Reward, StorageKey, TodoEvent, TodoListV1, TodoListV1Ext};
+
+/// Requests not approved or rejected within this time are released back to
+/// the requester by `release_expired_redemptions`.
+pub const REDEMPTION_EXPIRY: u64 = 7 * NANOS_PER_DAY;
+
+/// A redemption waiting for the reward's approver. Its cost is held in escrow,
+/// out of the requester's balance, until the request is resolved.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct RedemptionRequest {
+    id: u64,
+    requester: AccountIdWrapper,
+    reward_id: u64,
+    cost: i64,
+    approver: AccountIdWrapper,
+    /// Account the redemption badge goes to once approved.
+    badge_owner: AccountIdWrapper,
+    requested_at: u64,
+    expires_at: u64,
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Makes redeeming the reward go through `request_redemption` and wait for
+    /// `approver`, or allows redeeming it directly again when `None`. Setting
+    /// the first approver needs `ManageRewards`; after that only the current
+    /// approver or the contract owner can change it. An account can never
+    /// approve its own redemptions.
+    #[handle_result]
+    pub fn set_reward_approver(&mut self, id: u64, approver: Option<AccountId>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let caller_id = env::predecessor_account_id();
+        let account_id = account_id.unwrap_or_else(|| caller_id.clone());
+        let current = self.get_reward(account_id.clone(), id).ok_or(ContractError::RewardNotFound(id))?.approver;
+        match current {
+            Some(current) if current.0 != caller_id && caller_id != self.owner_id => return Err(ContractError::Unauthorized),
+            Some(_) => {}
+            None => {
+                self.authorize(Some(account_id.clone()), Permission::ManageRewards)?;
+            }
+        }
+        if approver.as_ref() == Some(&account_id) {
+            return Err(ContractError::SelfApproval);
+        }
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        let reward = self.reward_mut(&account_id, id)?;
+        reward.approver = approver.map(AccountIdWrapper);
+        TodoEvent::RewardUpdated { account_id: &account_id, reward }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Escrows the cost of a reward that needs approval and returns the ID of
+    /// the request.
+    #[handle_result]
+    pub fn request_redemption(&mut self, reward_id: u64, account_id: Option<AccountId>) -> Result<u64, ContractError> {
+        let account_id = self.authorize(account_id, Permission::RedeemRewards)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        let now = env::block_timestamp();
+        let reward = self.get_reward(account_id.clone(), reward_id).ok_or(ContractError::RewardNotFound(reward_id))?;
+        let approver = reward.approver.clone().ok_or(ContractError::NoApprover(reward_id))?;
+        self.check_reward_available(&account_id, &reward, now)?;
+        let available = self.get_account_reward_points(account_id.clone());
+        if available < reward.cost {
+            return Err(ContractError::InsufficientPoints { required: reward.cost, available });
+        }
+
+        let id = Self::take_next_id(&mut self.next_redemption_ids, &account_id);
+        let request = RedemptionRequest {
+            id,
+            requester: AccountIdWrapper(account_id.clone()),
+            reward_id,
+            cost: reward.cost,
+            approver: approver.clone(),
+            badge_owner: AccountIdWrapper(self.redemption_badge_owner(&account_id, env::predecessor_account_id())),
+            requested_at: now,
+            expires_at: now + REDEMPTION_EXPIRY,
+        };
+        TodoEvent::RedemptionRequested { account_id: &account_id, request: &request }.emit();
+        self.change_points(&account_id, -reward.cost, PointsChange::RedemptionEscrowed { request_id: id })?;
+        self.redemption_requests
+            .entry(account_id.clone())
+            .or_insert_with(|| UnorderedMap::new(StorageKey::AccountRedemptionRequests { account_hash: account_hash(&account_id) }))
+            .insert(id, request);
+        self.approver_requests.entry(approver.0).or_default().push((account_id.clone(), id));
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(id)
+    }
+
+    /// Grants a pending request, which mints the redemption badge like
+    /// `redeem_reward` and returns its token ID. Only the request's approver
+    /// can approve it, before it expires. Stock, cooldown and period limit
+    /// were already counted when the request was made.
+    #[handle_result]
+    pub fn approve_redemption(&mut self, requester_id: AccountId, request_id: u64) -> Result<String, ContractError> {
+        let initial_storage_usage = self.begin_storage_tracking(&requester_id)?;
+        let request = self.pending_request_for_approver(&requester_id, request_id)?;
+        let now = env::block_timestamp();
+        if now >= request.expires_at {
+            return Err(ContractError::RedemptionExpired(request_id));
+        }
+        let reward = self.get_reward(requester_id.clone(), request.reward_id).ok_or(ContractError::RewardNotFound(request.reward_id))?;
+        reward.check_open(now)?;
+
+        self.remove_redemption_request(&request);
+        self.reward_mut(&requester_id, request.reward_id)?.record_redemption(request.requested_at);
+        TodoEvent::RedemptionApproved { account_id: &requester_id, request_id }.emit();
+        TodoEvent::RewardRedeemed { account_id: &requester_id, reward_id: request.reward_id, cost: request.cost }.emit();
+        let token_id = self.mint_reward_badge(&request.badge_owner.0, &reward);
+        self.settle_storage(&requester_id, initial_storage_usage)?;
+        Ok(token_id)
+    }
+
+    /// Turns a pending request down and refunds its escrow. Only the request's
+    /// approver can reject it.
+    #[handle_result]
+    pub fn reject_redemption(&mut self, requester_id: AccountId, request_id: u64) -> Result<(), ContractError> {
+        let initial_storage_usage = self.begin_storage_tracking(&requester_id)?;
+        let request = self.pending_request_for_approver(&requester_id, request_id)?;
+
+        TodoEvent::RedemptionRejected { account_id: &requester_id, request_id }.emit();
+        self.refund_redemption(&request)?;
+        self.settle_storage(&requester_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Refunds the account's expired requests and returns how many there were.
+    /// Anyone can call it.
+    #[handle_result]
+    pub fn release_expired_redemptions(&mut self, account_id: AccountId) -> Result<u32, ContractError> {
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let now = env::block_timestamp();
+        let expired: Vec<RedemptionRequest> =
+            self.get_redemption_requests(account_id.clone()).into_iter().filter(|request| now >= request.expires_at).collect();
+
+        for request in &expired {
+            TodoEvent::RedemptionExpired { account_id: &account_id, request_id: request.id }.emit();
+            self.refund_redemption(request)?;
+        }
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(expired.len() as u32)
+    }
+
+    /// Pending requests made by the account, including expired ones not
+    /// released yet.
+    pub fn get_redemption_requests(&self, account_id: AccountId) -> Vec<RedemptionRequest> {
+        self.redemption_requests
+            .get(&account_id)
+            .map(|requests| requests.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Pending requests waiting for `approver_id`.
+    pub fn get_redemption_requests_to_approve(&self, approver_id: AccountId) -> Vec<RedemptionRequest> {
+        self.approver_requests
+            .get(&approver_id)
+            .map(|requests| {
+                requests
+                    .iter()
+                    .filter_map(|(requester_id, id)| self.redemption_requests.get(requester_id)?.get(id))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl TodoListV1 {
+    /// Checks the reward's limits at `now` with the account's pending
+    /// requests for it counted as redemptions made when they were requested.
+    pub(crate) fn check_reward_available(&self, account_id: &AccountId, reward: &Reward, now: u64) -> Result<(), ContractError> {
+        let pending: Vec<u64> = self
+            .redemption_requests
+            .get(account_id)
+            .map(|requests| {
+                requests
+                    .values()
+                    .filter(|request| request.reward_id == reward.id && now < request.expires_at)
+                    .map(|request| request.requested_at)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if pending.is_empty() {
+            return reward.check_available(now);
+        }
+        let mut reward = reward.clone();
+        for requested_at in pending {
+            reward.record_redemption(requested_at);
+        }
+        reward.check_available(now)
+    }
+
+    fn pending_request_for_approver(&self, requester_id: &AccountId, request_id: u64) -> Result<RedemptionRequest, ContractError> {
+        let request = self
+            .redemption_requests
+            .get(requester_id)
+            .and_then(|requests| requests.get(&request_id))
+            .cloned()
+            .ok_or(ContractError::RedemptionNotFound(request_id))?;
+        if request.approver.0 != env::predecessor_account_id() {
+            return Err(ContractError::Unauthorized);
+        }
+        Ok(request)
+    }
+
+    fn refund_redemption(&mut self, request: &RedemptionRequest) -> Result<(), ContractError> {
+        self.remove_redemption_request(request);
+        let change = PointsChange::RedemptionRefunded { request_id: request.id };
+        self.change_points(&request.requester.0, request.cost, change)?;
+        Ok(())
+    }
+
+    fn remove_redemption_request(&mut self, request: &RedemptionRequest) {
+        if let Some(requests) = self.redemption_requests.get_mut(&request.requester.0) {
+            requests.remove(&request.id);
+        }
+        self.unindex_redemption_request(request);
+    }
+
+    /// Drops the request from its approver's list.
+    pub(crate) fn unindex_redemption_request(&mut self, request: &RedemptionRequest) {
+        let requester_id = &request.requester.0;
+        let now_empty = self.approver_requests.get_mut(&request.approver.0).is_some_and(|requests| {
+            requests.retain(|(account_id, id)| !(account_id == requester_id && *id == request.id));
+            requests.is_empty()
+        });
+        if now_empty {
+            self.approver_requests.remove(&request.approver.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use crate::RewardLimits;
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    fn setup_reward(contract: &mut TodoListV1) -> u64 {
+        contract.adjust_points(accounts(1), 100, "Allowance".to_string()).unwrap();
+        let reward = contract.add_reward("Game night".to_string(), "".to_string(), 40, None).unwrap();
+        contract.set_reward_approver(reward, Some(accounts(2)), None).unwrap();
+        reward
+    }
+
+    #[test]
+    fn test_approve_and_reject() {
+        let mut contract = setup_contract(accounts(1));
+        let reward = setup_reward(&mut contract);
+        assert_eq!(contract.redeem_reward(reward, None), Err(ContractError::ApprovalRequired(reward)));
+
+        let first = contract.request_redemption(reward, None).unwrap();
+        let second = contract.request_redemption(reward, None).unwrap();
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 20);
+        assert_eq!(contract.get_redemption_requests(accounts(1)).len(), 2);
+        assert_eq!(contract.get_redemption_requests_to_approve(accounts(2)).len(), 2);
+        assert_eq!(contract.approve_redemption(accounts(1), first), Err(ContractError::Unauthorized));
+
+        testing_env!(get_context(accounts(2)).build());
+        let token_id = contract.approve_redemption(accounts(1), first).unwrap();
+        assert_eq!(contract.nft_tokens_for_owner(accounts(1), None, None), vec![contract.nft_token(token_id).unwrap()]);
+        contract.reject_redemption(accounts(1), second).unwrap();
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 60);
+        assert_eq!(contract.ft_total_supply(), U128(60));
+        assert!(contract.get_redemption_requests_to_approve(accounts(2)).is_empty());
+        assert_eq!(contract.reject_redemption(accounts(1), second), Err(ContractError::RedemptionNotFound(second)));
+    }
+
+    #[test]
+    fn test_expired_requests_are_released() {
+        let mut contract = setup_contract(accounts(1));
+        let reward = setup_reward(&mut contract);
+        let request = contract.request_redemption(reward, None).unwrap();
+
+        testing_env!(get_context(accounts(2)).block_timestamp(REDEMPTION_EXPIRY).build());
+        assert_eq!(contract.approve_redemption(accounts(1), request), Err(ContractError::RedemptionExpired(request)));
+        assert_eq!(contract.release_expired_redemptions(accounts(1)), Ok(1));
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 100);
+        assert!(contract.get_redemption_requests(accounts(1)).is_empty());
+    }
+
+    #[test]
+    fn test_only_approver_changes_approver() {
+        // Bob owns the contract; Danny's rewards are approved by Charlie.
+        let mut contract = setup_contract(accounts(1));
+        testing_env!(get_context(accounts(3)).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+        testing_env!(get_context(accounts(3)).build());
+        let reward = contract.add_reward("Game night".to_string(), "".to_string(), 40, None).unwrap();
+        assert_eq!(contract.set_reward_approver(reward, Some(accounts(3)), None), Err(ContractError::SelfApproval));
+        contract.set_reward_approver(reward, Some(accounts(2)), None).unwrap();
+        assert_eq!(contract.set_reward_approver(reward, None, None), Err(ContractError::Unauthorized));
+        assert_eq!(contract.set_reward_approver(reward, Some(accounts(4)), None), Err(ContractError::Unauthorized));
+
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(contract.set_reward_approver(reward, Some(accounts(3)), Some(accounts(3))), Err(ContractError::SelfApproval));
+        contract.set_reward_approver(reward, Some(accounts(4)), Some(accounts(3))).unwrap();
+        assert_eq!(contract.set_reward_approver(reward, None, Some(accounts(3))), Err(ContractError::Unauthorized));
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_reward_approver(reward, None, Some(accounts(3))).unwrap();
+        assert_eq!(contract.get_reward(accounts(3), reward).unwrap().approver, None);
+    }
+
+    #[test]
+    fn test_pending_requests_hold_stock_and_cooldown() {
+        let mut contract = setup_contract(accounts(1));
+        let reward = setup_reward(&mut contract);
+        let limits = RewardLimits { stock: Some(1), ..Default::default() };
+        contract.set_reward_limits(reward, limits, None).unwrap();
+        let request = contract.request_redemption(reward, None).unwrap();
+        assert_eq!(contract.request_redemption(reward, None), Err(ContractError::RewardOutOfStock(reward)));
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.reject_redemption(accounts(1), request).unwrap();
+        let limits = RewardLimits { cooldown: Some(NANOS_PER_DAY), ..Default::default() };
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_reward_limits(reward, limits, None).unwrap();
+        let request = contract.request_redemption(reward, None).unwrap();
+        assert_eq!(contract.request_redemption(reward, None), Err(ContractError::RewardOnCooldown { id: reward, available_at: NANOS_PER_DAY }));
+        assert!(contract.get_redeemable_rewards(accounts(1)).is_empty());
+
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_DAY).build());
+        contract.approve_redemption(accounts(1), request).unwrap();
+        testing_env!(get_context(accounts(1)).block_timestamp(NANOS_PER_DAY).build());
+        contract.request_redemption(reward, None).unwrap();
+    }
+}
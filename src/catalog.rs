@@ -0,0 +1,240 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{ContractError, Permission, Reward, TodoEvent, TodoListV1, TodoListV1Ext};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug)]
+pub struct PeriodLimit {
+    pub count: u32,
+    /// Length of the sliding window, in nanoseconds.
+    pub period: u64,
+}
+
+/// Restrictions on redeeming a reward. Unset fields restrict nothing.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct RewardLimits {
+    /// Redemptions allowed over the reward's lifetime.
+    pub stock: Option<u32>,
+    /// At most `count` redemptions within any `period`.
+    pub per_period: Option<PeriodLimit>,
+    /// Nanoseconds that must pass between two redemptions.
+    pub cooldown: Option<u64>,
+    pub valid_from: Option<u64>,
+    pub valid_until: Option<u64>,
+}
+
+impl RewardLimits {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.per_period.is_some_and(|limit| limit.count == 0 || limit.period == 0) {
+            return Err(ContractError::InvalidRewardLimits("period limits need a positive count and period"));
+        }
+        if let (Some(valid_from), Some(valid_until)) = (self.valid_from, self.valid_until) {
+            if valid_from >= valid_until {
+                return Err(ContractError::InvalidRewardLimits("valid_from must be before valid_until"));
+            }
+        }
+        Ok(())
+    }
+
+    /// How long past redemptions matter for the cooldown and period limit.
+    fn memory(&self) -> u64 {
+        self.per_period.map_or(0, |limit| limit.period).max(self.cooldown.unwrap_or(0))
+    }
+}
+
+impl Reward {
+    /// Checks the reward's limits at `now`, ignoring its cost.
+    pub(crate) fn check_available(&self, now: u64) -> Result<(), ContractError> {
+        self.check_open(now)?;
+        if self.limits.stock.is_some_and(|stock| self.redeemed >= stock) {
+            return Err(ContractError::RewardOutOfStock(self.id));
+        }
+
+        let after_cooldown = self.limits.cooldown.zip(self.recent_redemptions.last()).map(|(cooldown, last)| last.saturating_add(cooldown));
+        let after_period = self.limits.per_period.and_then(|limit| {
+            let in_window: Vec<u64> = self.recent_redemptions.iter().copied().filter(|at| now < at.saturating_add(limit.period)).collect();
+            let count = limit.count as usize;
+            (in_window.len() >= count).then(|| in_window[in_window.len() - count].saturating_add(limit.period))
+        });
+        match after_cooldown.max(after_period) {
+            Some(available_at) if now < available_at => Err(ContractError::RewardOnCooldown { id: self.id, available_at }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the reward is active and within its availability window,
+    /// leaving out stock, cooldown and period limit.
+    pub(crate) fn check_open(&self, now: u64) -> Result<(), ContractError> {
+        if !self.active {
+            return Err(ContractError::RewardInactive(self.id));
+        }
+        if self.limits.valid_from.is_some_and(|valid_from| now < valid_from)
+            || self.limits.valid_until.is_some_and(|valid_until| now > valid_until)
+        {
+            return Err(ContractError::RewardNotAvailable(self.id));
+        }
+        Ok(())
+    }
+
+    /// Counts a redemption made at `redeemed_at`, keeping the recent ones in
+    /// time order.
+    pub(crate) fn record_redemption(&mut self, redeemed_at: u64) {
+        self.redeemed += 1;
+        let memory = self.limits.memory();
+        let index = self.recent_redemptions.partition_point(|at| *at <= redeemed_at);
+        self.recent_redemptions.insert(index, redeemed_at);
+        self.recent_redemptions.retain(|at| memory > 0 && redeemed_at < at.saturating_add(memory));
+    }
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Replaces the reward's limits. Every redemption made so far counts
+    /// against the new stock, but only those recent enough to matter for the
+    /// old cooldown or period limit count against the new ones. Without
+    /// either, no redemption times are kept.
+    #[handle_result]
+    pub fn set_reward_limits(&mut self, id: u64, limits: RewardLimits, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageRewards)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        limits.validate()?;
+
+        let reward = self.reward_mut(&account_id, id)?;
+        reward.limits = limits;
+        TodoEvent::RewardUpdated { account_id: &account_id, reward }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Inactive rewards stay in the catalog but cannot be redeemed.
+    #[handle_result]
+    pub fn set_reward_active(&mut self, id: u64, active: bool, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageRewards)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        let reward = self.reward_mut(&account_id, id)?;
+        reward.active = active;
+        TodoEvent::RewardUpdated { account_id: &account_id, reward }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Rewards the account could redeem right now with its current points.
+    pub fn get_redeemable_rewards(&self, account_id: AccountId) -> Vec<Reward> {
+        let now = env::block_timestamp();
+        let points = self.get_account_reward_points(account_id.clone());
+        self.rewards
+            .get(&account_id)
+            .map(|rewards| {
+                rewards
+                    .values()
+                    .filter(|reward| reward.cost <= points && self.check_reward_available(&account_id, reward, now).is_ok())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl TodoListV1 {
+    pub(crate) fn reward_mut(&mut self, account_id: &AccountId, id: u64) -> Result<&mut Reward, ContractError> {
+        self.rewards
+            .get_mut(account_id)
+            .and_then(|rewards| rewards.get_mut(&id))
+            .ok_or(ContractError::RewardNotFound(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    const HOUR: u64 = 3_600_000_000_000;
+
+    fn redeem_at(contract: &mut TodoListV1, id: u64, timestamp: u64) -> Result<String, ContractError> {
+        testing_env!(get_context(accounts(1)).block_timestamp(timestamp).build());
+        contract.redeem_reward(id, None)
+    }
+
+    #[test]
+    fn test_stock_cooldown_and_period_limits() {
+        let mut contract = setup_contract(accounts(1));
+        contract.adjust_points(accounts(1), 100, "Test balance".to_string()).unwrap();
+        let coffee = contract.add_reward("Coffee".to_string(), "".to_string(), 1, None).unwrap();
+        let limits = RewardLimits {
+            stock: Some(3),
+            per_period: Some(PeriodLimit { count: 2, period: 24 * HOUR }),
+            cooldown: Some(HOUR),
+            ..Default::default()
+        };
+        contract.set_reward_limits(coffee, limits, None).unwrap();
+
+        redeem_at(&mut contract, coffee, 10 * HOUR).unwrap();
+        assert_eq!(redeem_at(&mut contract, coffee, 10 * HOUR + 1), Err(ContractError::RewardOnCooldown { id: coffee, available_at: 11 * HOUR }));
+        redeem_at(&mut contract, coffee, 11 * HOUR).unwrap();
+        assert_eq!(redeem_at(&mut contract, coffee, 12 * HOUR), Err(ContractError::RewardOnCooldown { id: coffee, available_at: 34 * HOUR }));
+        redeem_at(&mut contract, coffee, 34 * HOUR).unwrap();
+        assert_eq!(redeem_at(&mut contract, coffee, 100 * HOUR), Err(ContractError::RewardOutOfStock(coffee)));
+        assert!(contract.get_redeemable_rewards(accounts(1)).is_empty());
+    }
+
+    #[test]
+    fn test_limits_added_after_redemptions() {
+        let mut contract = setup_contract(accounts(1));
+        contract.adjust_points(accounts(1), 10, "Test balance".to_string()).unwrap();
+        let snack = contract.add_reward("Snack".to_string(), "".to_string(), 1, None).unwrap();
+        redeem_at(&mut contract, snack, HOUR).unwrap();
+        redeem_at(&mut contract, snack, 2 * HOUR).unwrap();
+
+        let daily = PeriodLimit { count: 1, period: 24 * HOUR };
+        contract.set_reward_limits(snack, RewardLimits { stock: Some(4), per_period: Some(daily), ..Default::default() }, None).unwrap();
+        redeem_at(&mut contract, snack, 3 * HOUR).unwrap();
+        assert_eq!(redeem_at(&mut contract, snack, 4 * HOUR), Err(ContractError::RewardOnCooldown { id: snack, available_at: 27 * HOUR }));
+
+        let two_days = PeriodLimit { count: 1, period: 48 * HOUR };
+        contract.set_reward_limits(snack, RewardLimits { stock: Some(4), per_period: Some(two_days), ..Default::default() }, None).unwrap();
+        assert_eq!(redeem_at(&mut contract, snack, 30 * HOUR), Err(ContractError::RewardOnCooldown { id: snack, available_at: 51 * HOUR }));
+        redeem_at(&mut contract, snack, 51 * HOUR).unwrap();
+        assert_eq!(redeem_at(&mut contract, snack, 200 * HOUR), Err(ContractError::RewardOutOfStock(snack)));
+    }
+
+    #[test]
+    fn test_huge_cooldown_and_period() {
+        let mut contract = setup_contract(accounts(1));
+        contract.adjust_points(accounts(1), 10, "Test balance".to_string()).unwrap();
+        let once = contract.add_reward("Once".to_string(), "".to_string(), 1, None).unwrap();
+        let limits = RewardLimits { per_period: Some(PeriodLimit { count: 1, period: u64::MAX }), cooldown: Some(u64::MAX), ..Default::default() };
+        contract.set_reward_limits(once, limits, None).unwrap();
+
+        redeem_at(&mut contract, once, HOUR).unwrap();
+        assert_eq!(redeem_at(&mut contract, once, 2 * HOUR), Err(ContractError::RewardOnCooldown { id: once, available_at: u64::MAX }));
+    }
+
+    #[test]
+    fn test_availability_window_and_active_flag() {
+        let mut contract = setup_contract(accounts(1));
+        contract.adjust_points(accounts(1), 10, "Test balance".to_string()).unwrap();
+        let cheap = contract.add_reward("Cheap".to_string(), "".to_string(), 5, None).unwrap();
+        let pricey = contract.add_reward("Pricey".to_string(), "".to_string(), 50, None).unwrap();
+        let limits = RewardLimits { valid_from: Some(HOUR), valid_until: Some(2 * HOUR), ..Default::default() };
+        contract.set_reward_limits(cheap, limits, None).unwrap();
+        assert_eq!(
+            contract.set_reward_limits(pricey, RewardLimits { valid_from: Some(HOUR), valid_until: Some(HOUR), ..Default::default() }, None),
+            Err(ContractError::InvalidRewardLimits("valid_from must be before valid_until"))
+        );
+
+        assert_eq!(redeem_at(&mut contract, cheap, 0), Err(ContractError::RewardNotAvailable(cheap)));
+        testing_env!(get_context(accounts(1)).block_timestamp(HOUR).build());
+        let redeemable: Vec<u64> = contract.get_redeemable_rewards(accounts(1)).iter().map(|reward| reward.id).collect();
+        assert_eq!(redeemable, vec![cheap]);
+
+        contract.set_reward_active(cheap, false, None).unwrap();
+        assert_eq!(redeem_at(&mut contract, cheap, HOUR), Err(ContractError::RewardInactive(cheap)));
+    }
+}
@@ -0,0 +1,213 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::UnorderedMap;
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{ContractError, Permission, Task, TodoEvent, TodoListV1, TodoListV1Ext};
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct TaskProgress {
+    completed: u32,
+    total: u32,
+}
+
+/// Whether `target` can be reached from `from` by following `depends_on` links.
+fn depends_transitively(tasks: &UnorderedMap<u64, Task>, from: u64, target: u64) -> bool {
+    let mut stack = vec![from];
+    let mut visited = vec![];
+    while let Some(id) = stack.pop() {
+        if id == target {
+            return true;
+        }
+        if visited.contains(&id) {
+            continue;
+        }
+        visited.push(id);
+        if let Some(task) = tasks.get(&id) {
+            stack.extend(&task.depends_on);
+        }
+    }
+    false
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Makes the task a subtask of `parent_id`, or a top-level task when `None`.
+    #[handle_result]
+    pub fn set_task_parent(&mut self, id: u64, parent_id: Option<u64>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        if let Some(parent_id) = parent_id {
+            let tasks = self.tasks.get(&account_id).ok_or(ContractError::TaskNotFound(id))?;
+            if !tasks.contains_key(&id) {
+                return Err(ContractError::TaskNotFound(id));
+            }
+            let mut ancestor = Some(parent_id);
+            while let Some(ancestor_id) = ancestor {
+                if ancestor_id == id {
+                    return Err(ContractError::DependencyCycle);
+                }
+                ancestor = tasks.get(&ancestor_id).ok_or(ContractError::TaskNotFound(ancestor_id))?.parent_id;
+            }
+        }
+
+        let task = self.task_mut(&account_id, id)?;
+        task.parent_id = parent_id;
+        TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Replaces the tasks that must be completed before this one can be.
+    #[handle_result]
+    pub fn set_task_dependencies(&mut self, id: u64, depends_on: Vec<u64>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let mut depends_on = depends_on;
+        depends_on.sort_unstable();
+        depends_on.dedup();
+
+        let tasks = self.tasks.get(&account_id).ok_or(ContractError::TaskNotFound(id))?;
+        if !tasks.contains_key(&id) {
+            return Err(ContractError::TaskNotFound(id));
+        }
+        for dependency in &depends_on {
+            if !tasks.contains_key(dependency) {
+                return Err(ContractError::TaskNotFound(*dependency));
+            }
+            if depends_transitively(tasks, *dependency, id) {
+                return Err(ContractError::DependencyCycle);
+            }
+        }
+
+        let task = self.task_mut(&account_id, id)?;
+        task.depends_on = depends_on;
+        TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    pub fn get_subtasks(&self, account_id: AccountId, id: u64) -> Vec<Task> {
+        self.tasks
+            .get(&account_id)
+            .map(|tasks| tasks.values().filter(|task| task.parent_id == Some(id)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Incomplete tasks that cannot be completed yet because of their dependencies.
+    pub fn get_blocked_tasks(&self, account_id: AccountId) -> Vec<Task> {
+        self.tasks
+            .get(&account_id)
+            .map(|tasks| {
+                tasks
+                    .values()
+                    .filter(|task| !task.completed && !self.pending_dependencies(&account_id, task).is_empty())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Completion of the task's direct subtasks.
+    pub fn get_task_progress(&self, account_id: AccountId, id: u64) -> Option<TaskProgress> {
+        self.get_task(account_id.clone(), id)?;
+        let subtasks = self.get_subtasks(account_id, id);
+        Some(TaskProgress {
+            completed: subtasks.iter().filter(|task| task.completed).count() as u32,
+            total: subtasks.len() as u32,
+        })
+    }
+}
+
+impl TodoListV1 {
+    /// IDs of the task's dependencies that are not completed yet. Dependencies
+    /// that were removed no longer block it.
+    pub(crate) fn pending_dependencies(&self, account_id: &AccountId, task: &Task) -> Vec<u64> {
+        let Some(tasks) = self.tasks.get(account_id) else {
+            return vec![];
+        };
+        task.depends_on
+            .iter()
+            .filter(|dependency| tasks.get(dependency).is_some_and(|dependency| !dependency.completed))
+            .copied()
+            .collect()
+    }
+
+    /// Drops references to a removed task: its subtasks become top-level and it
+    /// is no longer a dependency of anything.
+    pub(crate) fn unlink_task(&mut self, account_id: &AccountId, id: u64) {
+        let Some(tasks) = self.tasks.get_mut(account_id) else {
+            return;
+        };
+        let linked: Vec<u64> = tasks
+            .values()
+            .filter(|task| task.parent_id == Some(id) || task.depends_on.contains(&id))
+            .map(|task| task.id)
+            .collect();
+        for linked_id in linked {
+            if let Some(task) = tasks.get_mut(&linked_id) {
+                if task.parent_id == Some(id) {
+                    task.parent_id = None;
+                }
+                task.depends_on.retain(|dependency| *dependency != id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_contract;
+    use near_sdk::test_utils::accounts;
+
+    fn add(contract: &mut TodoListV1, title: &str) -> u64 {
+        contract.add_task(title.to_string(), "".to_string(), 1, None, None, 10, None).unwrap()
+    }
+
+    #[test]
+    fn test_dependencies_block_completion() {
+        let mut contract = setup_contract(accounts(1));
+        let design = add(&mut contract, "Design");
+        let build = add(&mut contract, "Build");
+        let ship = add(&mut contract, "Ship");
+        contract.set_task_dependencies(build, vec![design], None).unwrap();
+        contract.set_task_dependencies(ship, vec![build, design, build], None).unwrap();
+
+        assert_eq!(contract.get_task(accounts(1), ship).unwrap().depends_on, vec![design, build]);
+        assert_eq!(contract.set_task_dependencies(design, vec![ship], None), Err(ContractError::DependencyCycle));
+        assert_eq!(contract.set_task_dependencies(design, vec![design], None), Err(ContractError::DependencyCycle));
+        assert_eq!(contract.set_task_dependencies(design, vec![42], None), Err(ContractError::TaskNotFound(42)));
+
+        assert_eq!(contract.mark_complete(ship, None), Err(ContractError::DependenciesIncomplete(vec![design, build])));
+        let blocked: Vec<u64> = contract.get_blocked_tasks(accounts(1)).iter().map(|task| task.id).collect();
+        assert_eq!(blocked.len(), 2);
+        assert!(blocked.contains(&build) && blocked.contains(&ship));
+
+        contract.mark_complete(design, None).unwrap();
+        contract.remove_task(build, None).unwrap();
+        assert_eq!(contract.get_task(accounts(1), ship).unwrap().depends_on, vec![design]);
+        contract.mark_complete(ship, None).unwrap();
+        assert!(contract.get_blocked_tasks(accounts(1)).is_empty());
+    }
+
+    #[test]
+    fn test_subtasks_and_progress() {
+        let mut contract = setup_contract(accounts(1));
+        let project = add(&mut contract, "Project");
+        let first = add(&mut contract, "First step");
+        let second = add(&mut contract, "Second step");
+        contract.set_task_parent(first, Some(project), None).unwrap();
+        contract.set_task_parent(second, Some(project), None).unwrap();
+        contract.mark_complete(first, None).unwrap();
+
+        assert_eq!(contract.get_subtasks(accounts(1), project).len(), 2);
+        assert_eq!(contract.get_task_progress(accounts(1), project), Some(TaskProgress { completed: 1, total: 2 }));
+        assert_eq!(contract.get_task_progress(accounts(1), 42), None);
+        assert_eq!(contract.set_task_parent(project, Some(second), None), Err(ContractError::DependencyCycle));
+
+        contract.remove_task(project, None).unwrap();
+        assert_eq!(contract.get_task(accounts(1), second).unwrap().parent_id, None);
+    }
+}
@@ -0,0 +1,142 @@
+use near_sdk::{AccountId, Balance, FunctionError};
+use std::fmt;
+
+use crate::time::{MAX_UTC_OFFSET, MIN_UTC_OFFSET};
+use crate::{DurationMinutes, TimeOfDay};
+
+/// Failure reasons returned by `TodoListV1` methods. The `Display` text is the
+/// panic message seen by callers.
+#[derive(FunctionError, Clone, PartialEq, Debug)]
+pub enum ContractError {
+    TaskNotFound(u64),
+    RewardNotFound(u64),
+    ProjectNotFound(u64),
+    TagNotFound(u64),
+    NftNotFound(String),
+    RedemptionNotFound(u64),
+    BreakNotFound,
+    AlreadyCompleted(u64),
+    NotCompleted(u64),
+    VerificationRequired(u64),
+    NotSupervised(u64),
+    NotSubmitted(u64),
+    VerifiedTask(u64),
+    InsufficientPoints { required: i64, available: i64 },
+    PointsOverflow,
+    RewardInactive(u64),
+    RewardNotAvailable(u64),
+    RewardOutOfStock(u64),
+    RewardOnCooldown { id: u64, available_at: u64 },
+    ApprovalRequired(u64),
+    NoApprover(u64),
+    SelfApproval,
+    RedemptionExpired(u64),
+    ZeroAmount,
+    SelfTransfer,
+    InsufficientGas { required: u64 },
+    InvalidTimeRange { start_time: TimeOfDay, end_time: TimeOfDay },
+    OverlappingTimeSlots,
+    EmptyTitle,
+    EmptyName,
+    InvalidColor(String),
+    TextTooLong { field: &'static str, max: u32 },
+    PriorityOutOfRange { priority: u8, min: u8, max: u8 },
+    NegativeValue { field: &'static str, value: i64 },
+    ValueTooLarge { field: &'static str, value: i64, max: i64 },
+    InvalidDuration(DurationMinutes),
+    TaskLimitReached { max: u32 },
+    InvalidLimits(&'static str),
+    InvalidRewardLimits(&'static str),
+    InvalidWeekStart(u64),
+    InvalidUtcOffset(i16),
+    InvalidRecurrence(&'static str),
+    DependencyCycle,
+    DependenciesIncomplete(Vec<u64>),
+    NotRegistered(AccountId),
+    InsufficientStorageBalance { required: Balance, available: Balance },
+    AccountHasData,
+    GrantNotFound(AccountId),
+    InvalidWorkspaceName(String),
+    WorkspaceExists(AccountId),
+    NotAMember(AccountId),
+    LastWorkspaceOwner,
+    Unauthorized,
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::TaskNotFound(id) => write!(f, "Task {} not found", id),
+            ContractError::RewardNotFound(id) => write!(f, "Reward {} not found", id),
+            ContractError::ProjectNotFound(id) => write!(f, "Project {} not found", id),
+            ContractError::TagNotFound(id) => write!(f, "Tag {} not found", id),
+            ContractError::NftNotFound(token_id) => write!(f, "Token {} not found", token_id),
+            ContractError::RedemptionNotFound(id) => write!(f, "Redemption request {} not found", id),
+            ContractError::BreakNotFound => write!(f, "Break not found"),
+            ContractError::AlreadyCompleted(id) => write!(f, "Task {} is already completed", id),
+            ContractError::NotCompleted(id) => write!(f, "Task {} is not completed", id),
+            ContractError::VerificationRequired(id) => write!(f, "Task {} must be verified by its supervisor, call submit_task", id),
+            ContractError::NotSupervised(id) => write!(f, "Task {} has no supervisor, complete it directly", id),
+            ContractError::NotSubmitted(id) => write!(f, "Task {} has not been submitted", id),
+            ContractError::VerifiedTask(id) => write!(f, "Task {} was verified by its supervisor and cannot be reopened", id),
+            ContractError::InsufficientPoints { required, available } => {
+                write!(f, "Insufficient points: {} required, {} available", required, available)
+            }
+            ContractError::PointsOverflow => write!(f, "The balance or total supply of points would overflow"),
+            ContractError::RewardInactive(id) => write!(f, "Reward {} is not active", id),
+            ContractError::RewardNotAvailable(id) => write!(f, "Reward {} is outside its availability window", id),
+            ContractError::RewardOutOfStock(id) => write!(f, "Reward {} is out of stock", id),
+            ContractError::RewardOnCooldown { id, available_at } => {
+                write!(f, "Reward {} cannot be redeemed again before {}", id, available_at)
+            }
+            ContractError::ApprovalRequired(id) => write!(f, "Reward {} needs approval, call request_redemption", id),
+            ContractError::NoApprover(id) => write!(f, "Reward {} has no approver, redeem it directly", id),
+            ContractError::SelfApproval => write!(f, "An account cannot approve its own redemptions"),
+            ContractError::RedemptionExpired(id) => write!(f, "Redemption request {} has expired", id),
+            ContractError::ZeroAmount => write!(f, "The amount must be positive"),
+            ContractError::SelfTransfer => write!(f, "Sender and receiver must be different"),
+            ContractError::InsufficientGas { required } => write!(f, "At least {} gas must be attached", required),
+            ContractError::InvalidTimeRange { start_time, end_time } => {
+                write!(f, "Invalid time range: {}-{} must be a non-empty range within the day", start_time, end_time)
+            }
+            ContractError::OverlappingTimeSlots => write!(f, "Time slots on the same day must not overlap"),
+            ContractError::EmptyTitle => write!(f, "Title must not be empty"),
+            ContractError::EmptyName => write!(f, "Name must not be empty"),
+            ContractError::InvalidColor(color) => write!(f, "Invalid colour {:?}, expected #RRGGBB", color),
+            ContractError::TextTooLong { field, max } => write!(f, "The {} must be at most {} characters", field, max),
+            ContractError::PriorityOutOfRange { priority, min, max } => {
+                write!(f, "Priority {} is outside the allowed range {}-{}", priority, min, max)
+            }
+            ContractError::NegativeValue { field, value } => write!(f, "The {} must not be negative, got {}", field, value),
+            ContractError::ValueTooLarge { field, value, max } => write!(f, "The {} must be at most {}, got {}", field, max, value),
+            ContractError::InvalidDuration(duration) => write!(f, "Invalid duration: {}", duration),
+            ContractError::TaskLimitReached { max } => write!(f, "Task limit of {} per account reached", max),
+            ContractError::InvalidLimits(reason) => write!(f, "Invalid validation limits: {}", reason),
+            ContractError::InvalidRewardLimits(reason) => write!(f, "Invalid reward limits: {}", reason),
+            ContractError::InvalidWeekStart(timestamp) => {
+                write!(f, "Week start {} must be midnight on a Monday (UTC)", timestamp)
+            }
+            ContractError::InvalidUtcOffset(offset) => {
+                write!(f, "UTC offset of {} minutes is outside the range {} to {}", offset, MIN_UTC_OFFSET, MAX_UTC_OFFSET)
+            }
+            ContractError::InvalidRecurrence(reason) => write!(f, "Invalid recurrence: {}", reason),
+            ContractError::DependencyCycle => write!(f, "Tasks must not depend on or contain themselves"),
+            ContractError::DependenciesIncomplete(ids) => write!(f, "Tasks {:?} must be completed first", ids),
+            ContractError::NotRegistered(account_id) => {
+                write!(f, "Account {} is not registered, call storage_deposit first", account_id)
+            }
+            ContractError::InsufficientStorageBalance { required, available } => {
+                write!(f, "Insufficient storage balance: {} yoctoNEAR required, {} available", required, available)
+            }
+            ContractError::AccountHasData => write!(f, "Account still has data, pass force to remove it"),
+            ContractError::GrantNotFound(account_id) => write!(f, "No access granted to {}", account_id),
+            ContractError::InvalidWorkspaceName(name) => {
+                write!(f, "Invalid workspace name {:?}, expected lowercase letters, digits, '-' or '_'", name)
+            }
+            ContractError::WorkspaceExists(workspace_id) => write!(f, "Workspace {} already exists", workspace_id),
+            ContractError::NotAMember(account_id) => write!(f, "{} is not a member of the workspace", account_id),
+            ContractError::LastWorkspaceOwner => write!(f, "A workspace must keep at least one owner"),
+            ContractError::Unauthorized => write!(f, "Caller is not allowed to perform this action"),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json;
+use near_sdk::{env, AccountId};
+
+use crate::{Break, Label, Permission, RedemptionRequest, Reward, Role, Task, TimeOfDay, TimeSlots, WeeklyWorkingHours};
+
+/// NEP-297 standard name for events emitted by this contract.
+pub const EVENT_STANDARD: &str = "todolist";
+/// Bumped whenever the `data` layout of an existing event changes.
+pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+/// Reward points are also a NEP-141 token, whose events follow that standard.
+pub const FT_EVENT_STANDARD: &str = "nep141";
+pub const FT_EVENT_STANDARD_VERSION: &str = "1.0.0";
+/// Badges are NEP-171 tokens, whose events follow that standard.
+pub const NFT_EVENT_STANDARD: &str = "nep171";
+pub const NFT_EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum TodoEvent<'a> {
+    TaskCreated { account_id: &'a AccountId, task: &'a Task },
+    TaskUpdated { account_id: &'a AccountId, task: &'a Task },
+    TaskCompleted { account_id: &'a AccountId, task_id: u64, reward_points: i64 },
+    TaskReopened { account_id: &'a AccountId, task_id: u64, reward_points: i64 },
+    TaskRemoved { account_id: &'a AccountId, task_id: u64 },
+    RewardCreated { account_id: &'a AccountId, reward: &'a Reward },
+    RewardUpdated { account_id: &'a AccountId, reward: &'a Reward },
+    RewardRemoved { account_id: &'a AccountId, reward_id: u64 },
+    RewardRedeemed { account_id: &'a AccountId, reward_id: u64, cost: i64 },
+    RedemptionRequested { account_id: &'a AccountId, request: &'a RedemptionRequest },
+    RedemptionApproved { account_id: &'a AccountId, request_id: u64 },
+    RedemptionRejected { account_id: &'a AccountId, request_id: u64 },
+    RedemptionExpired { account_id: &'a AccountId, request_id: u64 },
+    PointsChanged { account_id: &'a AccountId, old_balance: i64, new_balance: i64 },
+    BreakAdded { account_id: &'a AccountId, #[serde(rename = "break")] break_: &'a Break },
+    BreakUpdated { account_id: &'a AccountId, #[serde(rename = "break")] break_: &'a Break },
+    BreakRemoved { account_id: &'a AccountId, start_time: TimeOfDay, end_time: TimeOfDay, is_regular: bool, date: Option<u64> },
+    WorkingHoursUpdated { account_id: &'a AccountId, working_hours: &'a WeeklyWorkingHours },
+    TimeSlotsUpdated { account_id: &'a AccountId, time_slots: &'a TimeSlots },
+    UtcOffsetUpdated { account_id: &'a AccountId, utc_offset: i16 },
+    CompletedDaysRolledUp { account_id: &'a AccountId, before_day: u32, days: u32 },
+    AccessGranted { account_id: &'a AccountId, delegate: &'a AccountId, permissions: &'a [Permission] },
+    AccessRevoked { account_id: &'a AccountId, delegate: &'a AccountId },
+    ProjectCreated { account_id: &'a AccountId, project: &'a Label },
+    ProjectUpdated { account_id: &'a AccountId, project: &'a Label },
+    ProjectRemoved { account_id: &'a AccountId, project_id: u64 },
+    TagCreated { account_id: &'a AccountId, tag: &'a Label },
+    TagUpdated { account_id: &'a AccountId, tag: &'a Label },
+    TagRemoved { account_id: &'a AccountId, tag_id: u64 },
+    TaskAssigned { account_id: &'a AccountId, task_id: u64, assignee: Option<&'a AccountId> },
+    TaskSubmitted { account_id: &'a AccountId, task_id: u64, proof: Option<&'a str> },
+    TaskVerified { account_id: &'a AccountId, task_id: u64, supervisor_id: &'a AccountId },
+    TaskSubmissionRejected { account_id: &'a AccountId, task_id: u64, reason: Option<&'a str> },
+    WorkspaceCreated { account_id: &'a AccountId, owner_id: &'a AccountId },
+    MemberUpdated { account_id: &'a AccountId, member_id: &'a AccountId, role: Role },
+    MemberRemoved { account_id: &'a AccountId, member_id: &'a AccountId },
+    WorkspaceDeleted { account_id: &'a AccountId, owner_id: &'a AccountId },
+}
+
+/// NEP-141 token events. Each carries a single entry, though the standard
+/// allows batching several in `data`.
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum FtEvent<'a> {
+    FtMint([FtMint<'a>; 1]),
+    FtBurn([FtMint<'a>; 1]),
+    FtTransfer([FtTransfer<'a>; 1]),
+}
+
+#[derive(Serialize, Debug)]
+pub struct FtMint<'a> {
+    pub owner_id: &'a AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FtTransfer<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+/// NEP-171 badge events, one entry each like `FtEvent`.
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum NftEvent<'a> {
+    NftMint([NftMint<'a>; 1]),
+    NftBurn([NftMint<'a>; 1]),
+    NftTransfer([NftTransfer<'a>; 1]),
+}
+
+#[derive(Serialize, Debug)]
+pub struct NftMint<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_ids: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NftTransfer<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+    pub token_ids: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct EventLog<'a, E> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a E,
+}
+
+fn to_json<E: Serialize>(standard: &'static str, version: &'static str, event: &E) -> String {
+    serde_json::to_string(&EventLog { standard, version, event }).unwrap_or_else(|_| env::abort())
+}
+
+impl TodoEvent<'_> {
+    pub fn to_json(&self) -> String {
+        to_json(EVENT_STANDARD, EVENT_STANDARD_VERSION, self)
+    }
+
+    pub fn emit(&self) {
+        env::log_str(&format!("EVENT_JSON:{}", self.to_json()));
+    }
+}
+
+impl FtEvent<'_> {
+    pub fn to_json(&self) -> String {
+        to_json(FT_EVENT_STANDARD, FT_EVENT_STANDARD_VERSION, self)
+    }
+
+    pub fn emit(&self) {
+        env::log_str(&format!("EVENT_JSON:{}", self.to_json()));
+    }
+}
+
+impl NftEvent<'_> {
+    pub fn to_json(&self) -> String {
+        to_json(NFT_EVENT_STANDARD, NFT_EVENT_STANDARD_VERSION, self)
+    }
+
+    pub fn emit(&self) {
+        env::log_str(&format!("EVENT_JSON:{}", self.to_json()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn test_event_json_format() {
+        let account_id = accounts(1);
+        let event = TodoEvent::TaskCompleted { account_id: &account_id, task_id: 3, reward_points: 10 };
+
+        assert_eq!(
+            event.to_json(),
+            r#"{"standard":"todolist","version":"1.0.0","event":"task_completed","data":{"account_id":"bob","task_id":3,"reward_points":10}}"#
+        );
+
+        let event = FtEvent::FtMint([FtMint { owner_id: &account_id, amount: U128(10), memo: None }]);
+        assert_eq!(
+            event.to_json(),
+            r#"{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"bob","amount":"10"}]}"#
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json;
+use near_sdk::{assert_one_yocto, env, ext_contract, near_bindgen, AccountId, Gas, Promise, PromiseOrValue, PromiseResult};
+use schemars::JsonSchema;
+
+use crate::events::{FtEvent, FtTransfer};
+use crate::{AccountIdWrapper, ContractError, PointsChange, TodoListV1, TodoListV1Ext};
+
+/// Version of the NEP-148 metadata standard `ft_metadata` follows.
+pub const FT_METADATA_SPEC: &str = "ft-1.0.0";
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+/// Covers `ft_resolve_transfer` plus the cross-contract call overhead; the
+/// rest of the prepaid gas goes to `ft_on_transfer`.
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct FungibleTokenMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+    pub decimals: u8,
+}
+
+#[ext_contract(ext_ft_receiver)]
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128>;
+}
+
+/// Reward points as a NEP-141 token. Completing tasks mints them and redeeming
+/// rewards burns them; the receiver of a transfer must be registered through
+/// `storage_deposit` and pays for its own balance record.
+#[near_bindgen]
+impl TodoListV1 {
+    #[payable]
+    #[handle_result]
+    pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) -> Result<(), ContractError> {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.transfer_points(&sender_id, &receiver_id, amount, memo.as_deref())
+    }
+
+    /// Transfers to a contract and calls its `ft_on_transfer`, which returns the
+    /// amount it did not use. That part is refunded by `ft_resolve_transfer`.
+    #[payable]
+    #[handle_result]
+    pub fn ft_transfer_call(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>, msg: String) -> Result<Promise, ContractError> {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.transfer_points(&sender_id, &receiver_id, amount, memo.as_deref())?;
+
+        let remaining_gas = env::prepaid_gas() - env::used_gas();
+        if remaining_gas < GAS_FOR_FT_TRANSFER_CALL {
+            return Err(ContractError::InsufficientGas { required: GAS_FOR_FT_TRANSFER_CALL.0 });
+        }
+        let receiver_gas = remaining_gas - GAS_FOR_FT_TRANSFER_CALL;
+        Ok(ext_ft_receiver::ext(receiver_id.clone())
+            .with_static_gas(receiver_gas)
+            .ft_on_transfer(sender_id.clone(), amount, msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount),
+            ))
+    }
+
+    /// Returns the amount the receiver kept. Unused points still held by the
+    /// receiver go back to the sender, or are burned if the sender unregistered.
+    #[private]
+    pub fn ft_resolve_transfer(&mut self, sender_id: AccountId, receiver_id: AccountId, amount: U128) -> U128 {
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value).map_or(amount.0, |unused| unused.0.min(amount.0)),
+            _ => amount.0,
+        };
+        let refund = i64::try_from(unused).unwrap_or(i64::MAX).min(self.get_account_reward_points(receiver_id.clone()));
+        if refund <= 0 {
+            return amount;
+        }
+
+        if self.storage_accounts.contains_key(&sender_id) {
+            // The refund is charged to each side like a transfer. Neither can
+            // fail the callback, so shortfalls only show in their balances.
+            // Balances never add up past the total supply, so moving points
+            // between them cannot overflow.
+            let initial_storage_usage = env::storage_usage();
+            let _ = self.move_points(&receiver_id, -refund, PointsChange::TransferOut { receiver_id: AccountIdWrapper(sender_id.clone()) });
+            let _ = self.settle_storage(&receiver_id, initial_storage_usage);
+            let initial_storage_usage = env::storage_usage();
+            let _ = self.move_points(&sender_id, refund, PointsChange::TransferIn { sender_id: AccountIdWrapper(receiver_id.clone()) });
+            let _ = self.settle_storage(&sender_id, initial_storage_usage);
+            FtEvent::FtTransfer([FtTransfer {
+                old_owner_id: &receiver_id,
+                new_owner_id: &sender_id,
+                amount: U128(refund as u128),
+                memo: Some("refund"),
+            }])
+            .emit();
+        } else {
+            let reason = format!("Refund to unregistered {} burned", sender_id);
+            let _ = self.change_points(&receiver_id, -refund, PointsChange::Adjustment { reason });
+        }
+        U128(amount.0 - refund as u128)
+    }
+
+    pub fn ft_total_supply(&self) -> U128 {
+        U128(self.total_points as u128)
+    }
+
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.get_account_reward_points(account_id) as u128)
+    }
+
+    pub fn ft_metadata(&self) -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Todo Reward Points".to_string(),
+            symbol: "POINTS".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 0,
+        }
+    }
+}
+
+impl TodoListV1 {
+    fn transfer_points(&mut self, sender_id: &AccountId, receiver_id: &AccountId, amount: U128, memo: Option<&str>) -> Result<(), ContractError> {
+        if amount.0 == 0 {
+            return Err(ContractError::ZeroAmount);
+        }
+        if sender_id == receiver_id {
+            return Err(ContractError::SelfTransfer);
+        }
+        if !self.storage_accounts.contains_key(receiver_id) {
+            return Err(ContractError::NotRegistered(receiver_id.clone()));
+        }
+        let available = self.get_account_reward_points(sender_id.clone());
+        let required = i64::try_from(amount.0).unwrap_or(i64::MAX);
+        if available < required {
+            return Err(ContractError::InsufficientPoints { required, available });
+        }
+
+        // Each side pays for its own ledger entry.
+        let initial_storage_usage = self.begin_storage_tracking(sender_id)?;
+        self.move_points(sender_id, -required, PointsChange::TransferOut { receiver_id: AccountIdWrapper(receiver_id.clone()) })?;
+        self.settle_storage(sender_id, initial_storage_usage)?;
+        let initial_storage_usage = self.begin_storage_tracking(receiver_id)?;
+        self.move_points(receiver_id, required, PointsChange::TransferIn { sender_id: AccountIdWrapper(sender_id.clone()) })?;
+        FtEvent::FtTransfer([FtTransfer { old_owner_id: sender_id, new_owner_id: receiver_id, amount, memo }]).emit();
+        self.settle_storage(receiver_id, initial_storage_usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+    fn register(contract: &mut TodoListV1, account_id: AccountId) {
+        testing_env!(get_context(account_id.clone()).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+    }
+
+    #[test]
+    fn test_points_are_minted_transferred_and_burned() {
+        let mut contract = setup_contract(accounts(1));
+        let task = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 30, None).unwrap();
+        let reward = contract.add_reward("Reward".to_string(), "".to_string(), 10, None).unwrap();
+        contract.mark_complete(task, None).unwrap();
+        assert_eq!(contract.ft_total_supply(), U128(30));
+
+        register(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        contract.ft_transfer(accounts(2), U128(15), Some("thanks".to_string())).unwrap();
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(15));
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(15));
+        assert_eq!(contract.ft_transfer(accounts(2), U128(16), None), Err(ContractError::InsufficientPoints { required: 16, available: 15 }));
+        assert_eq!(contract.ft_transfer(accounts(3), U128(1), None), Err(ContractError::NotRegistered(accounts(3))));
+        assert_eq!(contract.ft_transfer(accounts(1), U128(1), None), Err(ContractError::SelfTransfer));
+        assert_eq!(contract.ft_transfer(accounts(2), U128(0), None), Err(ContractError::ZeroAmount));
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.redeem_reward(reward, None).unwrap();
+        assert_eq!(contract.ft_total_supply(), U128(20));
+        assert_eq!(contract.ft_metadata().decimals, 0);
+
+        testing_env!(get_context(accounts(1)).attached_deposit(1).prepaid_gas(Gas(GAS_FOR_FT_TRANSFER_CALL.0 / 2)).build());
+        assert!(matches!(
+            contract.ft_transfer_call(accounts(2), U128(5), None, "".to_string()),
+            Err(ContractError::InsufficientGas { required }) if required == GAS_FOR_FT_TRANSFER_CALL.0
+        ));
+    }
+
+    #[test]
+    fn test_resolve_transfer_refunds_unused() {
+        let mut contract = setup_contract(accounts(1));
+        let task = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 30, None).unwrap();
+        contract.mark_complete(task, None).unwrap();
+        register(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        contract.ft_transfer(accounts(2), U128(30), None).unwrap();
+
+        testing_env!(
+            get_context(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"\"10\"".to_vec())]
+        );
+        assert_eq!(contract.ft_resolve_transfer(accounts(1), accounts(2), U128(30)), U128(20));
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(10));
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(20));
+        assert_eq!(contract.ft_total_supply(), U128(30));
+    }
+}
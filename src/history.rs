@@ -0,0 +1,217 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+
+use crate::time::{self, MAX_UTC_OFFSET, MIN_UTC_OFFSET};
+use crate::{ContractError, Permission, TodoEvent, TodoListV1, TodoListV1Ext};
+
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug)]
+pub struct DayCompletions {
+    /// Local day counted from the Unix epoch.
+    day: u32,
+    completed: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug)]
+pub struct MonthCompletions {
+    year: u32,
+    month: u32,
+    completed: u32,
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Sets the account's time zone as minutes from UTC. Completions count for
+    /// the local day from now on; days already recorded keep their counts.
+    #[handle_result]
+    pub fn set_utc_offset(&mut self, utc_offset: i16, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageSchedule)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        if !(MIN_UTC_OFFSET..=MAX_UTC_OFFSET).contains(&utc_offset) {
+            return Err(ContractError::InvalidUtcOffset(utc_offset));
+        }
+
+        TodoEvent::UtcOffsetUpdated { account_id: &account_id, utc_offset }.emit();
+        if utc_offset == 0 {
+            self.utc_offsets.remove(&account_id);
+        } else {
+            self.utc_offsets.insert(account_id.clone(), utc_offset);
+        }
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Minutes from UTC of the account's time zone, 0 unless set.
+    pub fn get_utc_offset(&self, account_id: AccountId) -> i16 {
+        self.utc_offsets.get(&account_id).copied().unwrap_or(0)
+    }
+
+    /// Moves the completions of every day before `before_day` into monthly
+    /// totals, freeing their storage, and returns how many days were rolled
+    /// up. Those days no longer show in the daily views, streaks or stats.
+    #[handle_result]
+    pub fn roll_up_completed_days(&mut self, before_day: u32, account_id: Option<AccountId>) -> Result<u32, ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        let old_days: Vec<(u32, u32)> = self
+            .completed_tasks_per_day
+            .get(&account_id)
+            .map(|days| days.iter().filter(|(day, _)| **day < before_day).map(|(day, count)| (*day, *count)).collect())
+            .unwrap_or_default();
+        for (day, count) in &old_days {
+            self.account_completed_tasks_per_day_mut(&account_id).remove(day);
+            *self.account_completed_tasks_per_month_mut(&account_id).entry(time::month_index(*day)).or_insert(0) += count;
+        }
+        if self.completed_tasks_per_day.get(&account_id).is_some_and(|days| days.is_empty()) {
+            self.completed_tasks_per_day.remove(&account_id);
+        }
+        let rolled_up = old_days.len() as u32;
+        TodoEvent::CompletedDaysRolledUp { account_id: &account_id, before_day, days: rolled_up }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(rolled_up)
+    }
+
+    /// Days from `from_day` through `to_day` with completions, in order. Days
+    /// rolled up into monthly totals are left out.
+    pub fn get_completed_tasks_between(&self, account_id: AccountId, from_day: u32, to_day: u32) -> Vec<DayCompletions> {
+        let Some(days) = self.completed_tasks_per_day.get(&account_id) else {
+            return vec![];
+        };
+        if from_day > to_day {
+            return vec![];
+        }
+        // Look the range up day by day when that is cheaper than a full scan.
+        let mut completions: Vec<DayCompletions> = if u64::from(to_day - from_day) < u64::from(days.len()) {
+            (from_day..=to_day).filter_map(|day| days.get(&day).map(|completed| DayCompletions { day, completed: *completed })).collect()
+        } else {
+            days.iter()
+                .filter(|(day, _)| (from_day..=to_day).contains(*day))
+                .map(|(day, completed)| DayCompletions { day: *day, completed: *completed })
+                .collect()
+        };
+        completions.sort_unstable_by_key(|completions| completions.day);
+        completions
+    }
+
+    /// Completions per month, oldest first, including both rolled-up months
+    /// and days not rolled up yet.
+    pub fn get_completed_tasks_per_month(&self, account_id: AccountId) -> Vec<MonthCompletions> {
+        let mut months: BTreeMap<u32, u32> = self
+            .completed_tasks_per_month
+            .get(&account_id)
+            .map(|months| months.iter().map(|(month, count)| (*month, *count)).collect())
+            .unwrap_or_default();
+        if let Some(days) = self.completed_tasks_per_day.get(&account_id) {
+            for (day, count) in days.iter() {
+                *months.entry(time::month_index(*day)).or_insert(0) += count;
+            }
+        }
+        months
+            .into_iter()
+            .map(|(index, completed)| {
+                let (year, month) = time::month_from_index(index);
+                MonthCompletions { year, month, completed }
+            })
+            .collect()
+    }
+}
+
+impl TodoListV1 {
+    /// The account's local day of a block timestamp.
+    pub(crate) fn local_day(&self, account_id: &AccountId, timestamp: u64) -> u32 {
+        time::local_day(timestamp, self.get_utc_offset(account_id.clone()))
+    }
+
+    pub(crate) fn today(&self, account_id: &AccountId) -> u32 {
+        self.local_day(account_id, env::block_timestamp())
+    }
+
+    /// Completions over the account's whole history, rolled up or not.
+    pub(crate) fn total_completed_tasks(&self, account_id: &AccountId) -> u32 {
+        self.completed_tasks_total.get(account_id).copied().unwrap_or(0)
+    }
+
+    /// Keeps the running total in step with the day and month counts. Rolling
+    /// days up moves completions without changing it.
+    pub(crate) fn add_to_completed_total(&mut self, account_id: &AccountId, delta: i32) {
+        let total = self.total_completed_tasks(account_id).saturating_add_signed(delta);
+        if total == 0 {
+            self.completed_tasks_total.remove(account_id);
+        } else {
+            self.completed_tasks_total.insert(account_id.clone(), total);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use crate::time::NANOS_PER_DAY;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    // 2022-01-31 and 2022-02-01.
+    const JAN_31: u32 = 19_023;
+    const FEB_1: u32 = 19_024;
+    const HOUR: u64 = 3_600_000_000_000;
+
+    fn complete_at(contract: &mut TodoListV1, timestamp: u64) -> u64 {
+        testing_env!(get_context(accounts(1)).block_timestamp(timestamp).build());
+        let id = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 1, None).unwrap();
+        contract.mark_complete(id, None).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_utc_offset_decides_the_day() {
+        let mut contract = setup_contract(accounts(1));
+        let late_evening = u64::from(JAN_31) * NANOS_PER_DAY + 23 * HOUR;
+        complete_at(&mut contract, late_evening);
+        assert_eq!(contract.set_utc_offset(15 * 60, None), Err(ContractError::InvalidUtcOffset(15 * 60)));
+        contract.set_utc_offset(2 * 60, None).unwrap();
+        complete_at(&mut contract, late_evening);
+
+        assert_eq!(
+            contract.get_completed_tasks_between(accounts(1), JAN_31, FEB_1),
+            vec![DayCompletions { day: JAN_31, completed: 1 }, DayCompletions { day: FEB_1, completed: 1 }]
+        );
+        assert_eq!(contract.get_completed_tasks_between(accounts(1), FEB_1, u32::MAX), vec![DayCompletions { day: FEB_1, completed: 1 }]);
+        assert!(contract.get_completed_tasks_between(accounts(1), FEB_1, JAN_31).is_empty());
+    }
+
+    #[test]
+    fn test_roll_up_into_months() {
+        let mut contract = setup_contract(accounts(1));
+        let january = complete_at(&mut contract, u64::from(JAN_31) * NANOS_PER_DAY);
+        complete_at(&mut contract, u64::from(JAN_31) * NANOS_PER_DAY + HOUR);
+        complete_at(&mut contract, u64::from(FEB_1) * NANOS_PER_DAY);
+
+        assert_eq!(contract.roll_up_completed_days(FEB_1, None), Ok(1));
+        assert!(contract.get_completed_tasks_between(accounts(1), 0, JAN_31).is_empty());
+        let months = vec![MonthCompletions { year: 2022, month: 1, completed: 2 }, MonthCompletions { year: 2022, month: 2, completed: 1 }];
+        assert_eq!(contract.get_completed_tasks_per_month(accounts(1)), months);
+        assert_eq!(contract.total_completed_tasks(&accounts(1)), 3);
+
+        // Reopening a task from a rolled-up day takes it off the month.
+        contract.mark_incomplete(january, None).unwrap();
+        assert_eq!(contract.get_completed_tasks_per_month(accounts(1))[0], MonthCompletions { year: 2022, month: 1, completed: 1 });
+        assert_eq!(contract.get_completed_tasks_per_day(accounts(1)).len(), 1);
+        assert_eq!(contract.total_completed_tasks(&accounts(1)), 2);
+    }
+
+    #[test]
+    fn test_reopen_after_utc_offset_change() {
+        let mut contract = setup_contract(accounts(1));
+        let late_evening = u64::from(JAN_31) * NANOS_PER_DAY + 23 * HOUR;
+        let id = complete_at(&mut contract, late_evening);
+        contract.set_utc_offset(2 * 60, None).unwrap();
+
+        contract.mark_incomplete(id, None).unwrap();
+        assert!(contract.get_completed_tasks_between(accounts(1), JAN_31, FEB_1).is_empty());
+        assert!(contract.get_completed_tasks_per_month(accounts(1)).is_empty());
+        assert_eq!(contract.total_completed_tasks(&accounts(1)), 0);
+    }
+}
@@ -0,0 +1,293 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::UnorderedMap;
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{account_hash, ContractError, Permission, StorageKey, TodoEvent, TodoListV1, TodoListV1Ext};
+
+/// A project or tag that tasks can be organized by.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct Label {
+    id: u64,
+    name: String,
+    /// `#RRGGBB` colour for display.
+    color: Option<String>,
+}
+
+/// What happens to a project's tasks when it is deleted.
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectTasksOnDelete {
+    /// Removes the tasks along with the project.
+    Remove,
+    /// Moves the tasks to another project, or out of any project when `None`.
+    Reassign(Option<u64>),
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    #[handle_result]
+    pub fn create_project(&mut self, name: String, color: Option<String>, account_id: Option<AccountId>) -> Result<u64, ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        self.limits.validate_label(&name, color.as_deref())?;
+        let id = Self::take_next_id(&mut self.next_project_ids, &account_id);
+
+        let project = Label { id, name, color };
+        TodoEvent::ProjectCreated { account_id: &account_id, project: &project }.emit();
+        self.account_projects_mut(&account_id).insert(id, project);
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(id)
+    }
+
+    #[handle_result]
+    pub fn update_project(&mut self, id: u64, name: String, color: Option<String>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        self.limits.validate_label(&name, color.as_deref())?;
+
+        let project = self
+            .projects
+            .get_mut(&account_id)
+            .and_then(|projects| projects.get_mut(&id))
+            .ok_or(ContractError::ProjectNotFound(id))?;
+        project.name = name;
+        project.color = color;
+        TodoEvent::ProjectUpdated { account_id: &account_id, project }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    #[handle_result]
+    pub fn delete_project(&mut self, id: u64, tasks: ProjectTasksOnDelete, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        if let ProjectTasksOnDelete::Reassign(Some(target)) = tasks {
+            if target == id || !self.has_project(&account_id, target) {
+                return Err(ContractError::ProjectNotFound(target));
+            }
+        }
+
+        self.projects
+            .get_mut(&account_id)
+            .and_then(|projects| projects.remove(&id))
+            .ok_or(ContractError::ProjectNotFound(id))?;
+        TodoEvent::ProjectRemoved { account_id: &account_id, project_id: id }.emit();
+
+        let task_ids: Vec<u64> = self
+            .tasks
+            .get(&account_id)
+            .map(|all| all.values().filter(|task| task.project_id == Some(id)).map(|task| task.id).collect())
+            .unwrap_or_default();
+        for task_id in task_ids {
+            match tasks {
+                ProjectTasksOnDelete::Remove => self.delete_task(&account_id, task_id)?,
+                ProjectTasksOnDelete::Reassign(project_id) => {
+                    let task = self.task_mut(&account_id, task_id)?;
+                    task.project_id = project_id;
+                    TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+                }
+            }
+        }
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    pub fn get_projects(&self, account_id: AccountId) -> Vec<Label> {
+        self.projects
+            .get(&account_id)
+            .map(|projects| projects.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[handle_result]
+    pub fn create_tag(&mut self, name: String, color: Option<String>, account_id: Option<AccountId>) -> Result<u64, ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        self.limits.validate_label(&name, color.as_deref())?;
+        let id = Self::take_next_id(&mut self.next_tag_ids, &account_id);
+
+        let tag = Label { id, name, color };
+        TodoEvent::TagCreated { account_id: &account_id, tag: &tag }.emit();
+        self.account_tags_mut(&account_id).insert(id, tag);
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(id)
+    }
+
+    #[handle_result]
+    pub fn update_tag(&mut self, id: u64, name: String, color: Option<String>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        self.limits.validate_label(&name, color.as_deref())?;
+
+        let tag = self
+            .tags
+            .get_mut(&account_id)
+            .and_then(|tags| tags.get_mut(&id))
+            .ok_or(ContractError::TagNotFound(id))?;
+        tag.name = name;
+        tag.color = color;
+        TodoEvent::TagUpdated { account_id: &account_id, tag }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Deletes a tag and takes it off every task that had it.
+    #[handle_result]
+    pub fn delete_tag(&mut self, id: u64, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        self.tags
+            .get_mut(&account_id)
+            .and_then(|tags| tags.remove(&id))
+            .ok_or(ContractError::TagNotFound(id))?;
+        TodoEvent::TagRemoved { account_id: &account_id, tag_id: id }.emit();
+
+        if let Some(tasks) = self.tasks.get_mut(&account_id) {
+            let task_ids: Vec<u64> = tasks.values().filter(|task| task.tag_ids.contains(&id)).map(|task| task.id).collect();
+            for task_id in task_ids {
+                if let Some(task) = tasks.get_mut(&task_id) {
+                    task.tag_ids.retain(|tag_id| *tag_id != id);
+                    TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+                }
+            }
+        }
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    pub fn get_tags(&self, account_id: AccountId) -> Vec<Label> {
+        self.tags
+            .get(&account_id)
+            .map(|tags| tags.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[handle_result]
+    pub fn set_task_project(&mut self, id: u64, project_id: Option<u64>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        if let Some(project_id) = project_id {
+            if !self.has_project(&account_id, project_id) {
+                return Err(ContractError::ProjectNotFound(project_id));
+            }
+        }
+
+        let task = self.task_mut(&account_id, id)?;
+        task.project_id = project_id;
+        TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    #[handle_result]
+    pub fn set_task_tags(&mut self, id: u64, tag_ids: Vec<u64>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let mut tag_ids = tag_ids;
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+        let tags = self.tags.get(&account_id);
+        if let Some(missing) = tag_ids.iter().find(|tag_id| !tags.is_some_and(|tags| tags.contains_key(tag_id))) {
+            return Err(ContractError::TagNotFound(*missing));
+        }
+
+        let task = self.task_mut(&account_id, id)?;
+        task.tag_ids = tag_ids;
+        TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+}
+
+impl TodoListV1 {
+    fn has_project(&self, account_id: &AccountId, id: u64) -> bool {
+        self.projects.get(account_id).is_some_and(|projects| projects.contains_key(&id))
+    }
+
+    fn account_projects_mut(&mut self, account_id: &AccountId) -> &mut UnorderedMap<u64, Label> {
+        self.projects.entry(account_id.clone()).or_insert_with(|| {
+            UnorderedMap::new(StorageKey::AccountProjects { account_hash: account_hash(account_id) })
+        })
+    }
+
+    fn account_tags_mut(&mut self, account_id: &AccountId) -> &mut UnorderedMap<u64, Label> {
+        self.tags.entry(account_id.clone()).or_insert_with(|| {
+            UnorderedMap::new(StorageKey::AccountTags { account_hash: account_hash(account_id) })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use crate::{Permission, TaskFilter};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    fn add(contract: &mut TodoListV1, title: &str) -> u64 {
+        contract.add_task(title.to_string(), "".to_string(), 1, None, None, 10, None).unwrap()
+    }
+
+    #[test]
+    fn test_projects_and_tags() {
+        let mut contract = setup_contract(accounts(1));
+        let home = contract.create_project("Home".to_string(), Some("#1E90FF".to_string()), None).unwrap();
+        let urgent = contract.create_tag("urgent".to_string(), None, None).unwrap();
+        let dishes = add(&mut contract, "Dishes");
+        let laundry = add(&mut contract, "Laundry");
+        contract.set_task_project(dishes, Some(home), None).unwrap();
+        contract.set_task_project(laundry, Some(home), None).unwrap();
+        contract.set_task_tags(laundry, vec![urgent, urgent], None).unwrap();
+
+        assert_eq!(
+            contract.create_tag("bad".to_string(), Some("blue".to_string()), None),
+            Err(ContractError::InvalidColor("blue".to_string()))
+        );
+        assert_eq!(contract.set_task_project(dishes, Some(42), None), Err(ContractError::ProjectNotFound(42)));
+        assert_eq!(contract.set_task_tags(dishes, vec![42], None), Err(ContractError::TagNotFound(42)));
+
+        let filtered = |contract: &TodoListV1, filter: TaskFilter| -> Vec<u64> {
+            contract.get_tasks_paged(accounts(1), None, None, Some(filter)).iter().map(|task| task.id).collect()
+        };
+        assert_eq!(filtered(&contract, TaskFilter { project_id: Some(home), ..Default::default() }), vec![dishes, laundry]);
+        assert_eq!(filtered(&contract, TaskFilter { tag_ids: vec![urgent], ..Default::default() }), vec![laundry]);
+
+        contract.update_tag(urgent, "asap".to_string(), Some("#ff0000".to_string()), None).unwrap();
+        assert_eq!(contract.get_tags(accounts(1))[0].name, "asap");
+        contract.delete_tag(urgent, None).unwrap();
+        assert!(contract.get_task(accounts(1), laundry).unwrap().tag_ids.is_empty());
+    }
+
+    #[test]
+    fn test_delete_project() {
+        let mut contract = setup_contract(accounts(1));
+        let home = contract.create_project("Home".to_string(), None, None).unwrap();
+        let work = contract.create_project("Work".to_string(), None, None).unwrap();
+        let dishes = add(&mut contract, "Dishes");
+        let report = add(&mut contract, "Report");
+        contract.set_task_project(dishes, Some(home), None).unwrap();
+        contract.set_task_project(report, Some(work), None).unwrap();
+
+        assert_eq!(
+            contract.delete_project(home, ProjectTasksOnDelete::Reassign(Some(home)), None),
+            Err(ContractError::ProjectNotFound(home))
+        );
+        contract.delete_project(home, ProjectTasksOnDelete::Reassign(Some(work)), None).unwrap();
+        assert_eq!(contract.get_task(accounts(1), dishes).unwrap().project_id, Some(work));
+
+        contract.grant_access(accounts(2), vec![Permission::ManageTasks]).unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        let homework = contract.add_supervised_task("Homework".to_string(), "".to_string(), 1, None, None, 10, accounts(1)).unwrap();
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_task_project(homework, Some(work), None).unwrap();
+
+        contract.delete_project(work, ProjectTasksOnDelete::Remove, None).unwrap();
+        assert!(contract.get_tasks(accounts(1)).is_empty());
+        assert!(contract.supervised_tasks.get(&accounts(2)).is_none());
+        assert!(contract.get_projects(accounts(1)).is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::Vector;
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::query::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::{account_hash, AccountIdWrapper, ContractError, StorageKey, TodoListV1, TodoListV1Ext};
+
+/// Why an account's points changed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PointsChange {
+    /// `owner` is the account the task belongs to, which differs from the
+    /// credited one for tasks assigned in a workspace.
+    TaskCompleted { owner: AccountIdWrapper, task_id: u64 },
+    TaskReopened { owner: AccountIdWrapper, task_id: u64 },
+    RewardRedeemed { reward_id: u64 },
+    /// The cost of a reward awaiting approval, held until the request is resolved.
+    RedemptionEscrowed { request_id: u64 },
+    RedemptionRefunded { request_id: u64 },
+    TransferIn { sender_id: AccountIdWrapper },
+    TransferOut { receiver_id: AccountIdWrapper },
+    Adjustment { reason: String },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct PointsLedgerEntry {
+    pub(crate) timestamp: u64,
+    /// Signed change actually applied, after clamping the balance at zero.
+    pub(crate) amount: i64,
+    /// Balance right after this entry.
+    pub(crate) balance: i64,
+    #[serde(flatten)]
+    pub(crate) change: PointsChange,
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Credits or debits an account outside of tasks and rewards, recorded with
+    /// `reason`. Only the contract owner can adjust balances.
+    #[handle_result]
+    pub fn adjust_points(&mut self, account_id: AccountId, amount: i64, reason: String) -> Result<i64, ContractError> {
+        if env::predecessor_account_id() != self.owner_id {
+            return Err(ContractError::Unauthorized);
+        }
+        if reason.trim().is_empty() {
+            return Err(ContractError::EmptyName);
+        }
+        self.limits.validate_adjustment(amount)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let balance = self.change_points(&account_id, amount, PointsChange::Adjustment { reason })?;
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(balance)
+    }
+
+    /// Returns one page of the account's ledger, oldest entry first. The
+    /// amounts of all entries add up to `get_account_reward_points`.
+    pub fn get_points_history(&self, account_id: AccountId, from_index: Option<u64>, limit: Option<u64>) -> Vec<PointsLedgerEntry> {
+        let Some(ledger) = self.points_ledger.get(&account_id) else {
+            return vec![];
+        };
+        ledger
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE) as usize)
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_points_history_length(&self, account_id: AccountId) -> u64 {
+        self.points_ledger.get(&account_id).map_or(0, |ledger| ledger.len().into())
+    }
+}
+
+impl TodoListV1 {
+    pub(crate) fn record_points_change(&mut self, account_id: &AccountId, amount: i64, balance: i64, change: PointsChange) {
+        let ledger = self.points_ledger.entry(account_id.clone()).or_insert_with(|| {
+            Vector::new(StorageKey::AccountPointsLedger { account_hash: account_hash(account_id) })
+        });
+        ledger.push(PointsLedgerEntry { timestamp: env::block_timestamp(), amount, balance, change });
+        // The ledger may belong to another account than the one settling the
+        // call's storage, so it is written out now to be counted.
+        ledger.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use crate::ValidationLimits;
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    fn history_sum(contract: &TodoListV1, account_id: AccountId) -> i64 {
+        contract.get_points_history(account_id, None, Some(MAX_PAGE_SIZE)).iter().map(|entry| entry.amount).sum()
+    }
+
+    #[test]
+    fn test_ledger_records_every_change() {
+        let mut contract = setup_contract(accounts(1));
+        let task = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 40, None).unwrap();
+        let reward = contract.add_reward("Reward".to_string(), "".to_string(), 15, None).unwrap();
+        contract.mark_complete(task, None).unwrap();
+        contract.redeem_reward(reward, None).unwrap();
+        testing_env!(get_context(accounts(2)).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        contract.ft_transfer(accounts(2), U128(5), None).unwrap();
+
+        let changes: Vec<PointsChange> = contract.get_points_history(accounts(1), None, None).into_iter().map(|entry| entry.change).collect();
+        assert_eq!(
+            changes,
+            vec![
+                PointsChange::TaskCompleted { owner: AccountIdWrapper(accounts(1)), task_id: task },
+                PointsChange::RewardRedeemed { reward_id: reward },
+                PointsChange::TransferOut { receiver_id: AccountIdWrapper(accounts(2)) },
+            ]
+        );
+        assert_eq!(history_sum(&contract, accounts(1)), contract.get_account_reward_points(accounts(1)));
+        assert_eq!(history_sum(&contract, accounts(2)), 5);
+        assert_eq!(contract.get_points_history(accounts(1), Some(2), None)[0].balance, 20);
+        assert_eq!(contract.get_points_history_length(accounts(1)), 3);
+    }
+
+    #[test]
+    fn test_adjust_points() {
+        // The contract owner is the account that called `new`.
+        let mut contract = setup_contract(accounts(1));
+        contract.adjust_points(accounts(1), 25, "Signup bonus".to_string()).unwrap();
+        assert_eq!(contract.adjust_points(accounts(1), -40, "Correction".to_string()), Ok(0));
+        assert_eq!(
+            contract.adjust_points(accounts(1), i64::MAX, "Overflow".to_string()),
+            Err(ContractError::ValueTooLarge { field: "amount", value: i64::MAX, max: 1_000_000 })
+        );
+
+        let history = contract.get_points_history(accounts(1), None, None);
+        assert_eq!(history.iter().map(|entry| entry.amount).collect::<Vec<_>>(), vec![25, -25]);
+        assert_eq!(contract.ft_total_supply(), U128(0));
+
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(contract.adjust_points(accounts(1), 10, "Bonus".to_string()), Err(ContractError::Unauthorized));
+    }
+
+    #[test]
+    fn test_points_overflow() {
+        let mut contract = setup_contract(accounts(1));
+        let limits = ValidationLimits { max_reward_points: i64::MAX, ..ValidationLimits::default() };
+        contract.set_validation_limits(limits).unwrap();
+        contract.adjust_points(accounts(1), i64::MAX, "Everything".to_string()).unwrap();
+        let task = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 1, None).unwrap();
+
+        assert_eq!(contract.adjust_points(accounts(1), 1, "More".to_string()), Err(ContractError::PointsOverflow));
+        assert_eq!(contract.mark_complete(task, None), Err(ContractError::PointsOverflow));
+        testing_env!(get_context(accounts(2)).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+        testing_env!(get_context(accounts(1)).build());
+        assert_eq!(contract.adjust_points(accounts(2), 1, "Bonus".to_string()), Err(ContractError::PointsOverflow));
+        assert_eq!(contract.ft_total_supply(), U128(i64::MAX as u128));
+    }
+}
@@ -1,9 +1,61 @@
+// The generated cross-contract `TodoListV1Ext` mirrors every method signature.
+#![allow(clippy::too_many_arguments)]
+
 use schemars::JsonSchema;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::{env, near_bindgen, AccountId};
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, BorshStorageKey, PanicOnDefault};
 use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::{LookupMap, UnorderedMap, UnorderedSet, Vector};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
+use events::FtMint;
+
+mod access;
+mod analytics;
+mod approval;
+mod catalog;
+mod dependencies;
+mod error;
+mod events;
+mod history;
+mod ft;
+mod labels;
+mod ledger;
+mod migration;
+mod nft;
+mod query;
+mod recurrence;
+mod scheduler;
+mod storage;
+mod supervision;
+mod time;
+mod validation;
+mod workspace;
+
+pub use access::{AccessGrant, Permission};
+pub use analytics::{PeriodStats, ProductivityStats, Streaks};
+pub use approval::RedemptionRequest;
+pub use catalog::{PeriodLimit, RewardLimits};
+pub use dependencies::TaskProgress;
+pub use error::ContractError;
+pub use events::{FtEvent, NftEvent, TodoEvent};
+pub use history::{DayCompletions, MonthCompletions};
+pub use ft::{FungibleTokenMetadata, FungibleTokenReceiver};
+pub use labels::{Label, ProjectTasksOnDelete};
+pub use ledger::{PointsChange, PointsLedgerEntry};
+pub use migration::VersionedTodoList;
+pub use nft::{NftContractMetadata, NonFungibleTokenReceiver, Token, TokenMetadata};
+pub use query::{TaskFilter, TaskSortKey};
+pub use recurrence::{Frequency, Recurrence, Weekday};
+pub use scheduler::ScheduleResult;
+pub use storage::{StorageBalance, StorageBalanceBounds};
+pub use supervision::Submission;
+pub use time::{DurationMinutes, TimeOfDay};
+pub use validation::ValidationLimits;
+pub use workspace::{Role, WorkspaceMember, WorkspaceMembership};
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct AccountIdWrapper(AccountId);
 
@@ -30,9 +82,34 @@ pub struct Task {
     description: String,
     priority: u8,
     deadline: Option<u64>,
-    estimated_time: Option<f64>,
+    estimated_time: Option<DurationMinutes>,
     reward_points: i64,
     completed: bool,
+    /// Block timestamp of the completion.
+    completed_at: Option<u64>,
+    /// Local day the completion was counted on, which reopening takes it back
+    /// from even if the time zone changed since.
+    completed_day: Option<u32>,
+    /// Account credited on completion and the amount, which reopening takes
+    /// back exactly.
+    credit: Option<(AccountIdWrapper, i64)>,
+    /// Time actually spent, logged with `set_actual_time`.
+    actual_time: Option<DurationMinutes>,
+    recurrence: Option<Recurrence>,
+    /// Occurrence spawned when the task was completed, reused if the task is
+    /// reopened and completed again.
+    next_occurrence: Option<u64>,
+    parent_id: Option<u64>,
+    /// Tasks that must be completed before this one.
+    depends_on: Vec<u64>,
+    project_id: Option<u64>,
+    tag_ids: Vec<u64>,
+    /// Member credited with the points on completion, instead of the account
+    /// the task belongs to.
+    assignee: Option<AccountIdWrapper>,
+    /// Account that has to verify the task before its points are credited.
+    supervisor: Option<AccountIdWrapper>,
+    submission: Option<Submission>,
     owner: AccountIdWrapper,
 }
 
@@ -42,12 +119,21 @@ pub struct Reward {
     title: String,
     description: String,
     cost: i64,
+    limits: RewardLimits,
+    active: bool,
+    /// Times the reward was redeemed, counted against `limits.stock`.
+    redeemed: u32,
+    /// Timestamps of the redemptions the cooldown and period limit still
+    /// depend on, oldest first.
+    recent_redemptions: Vec<u64>,
+    /// Account that has to approve redemptions, made through `request_redemption`.
+    approver: Option<AccountIdWrapper>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
 pub struct WorkingHours {
-    start_time: f64,
-    end_time: f64,
+    start_time: TimeOfDay,
+    end_time: TimeOfDay,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
@@ -61,10 +147,17 @@ pub struct WeeklyWorkingHours {
     sunday: WorkingHours,
 }
 
+impl WeeklyWorkingHours {
+    /// Days in week order, starting with Monday.
+    pub(crate) fn days(&self) -> [&WorkingHours; 7] {
+        [&self.monday, &self.tuesday, &self.wednesday, &self.thursday, &self.friday, &self.saturday, &self.sunday]
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
 pub struct TimeSlot {
-    start_time: f64,
-    end_time: f64,
+    start_time: TimeOfDay,
+    end_time: TimeOfDay,
     task_id: Option<u64>,
 }
 
@@ -79,10 +172,22 @@ pub struct TimeSlots {
     sunday: Vec<TimeSlot>,
 }
 
+impl TimeSlots {
+    /// Days in week order, starting with Monday.
+    pub(crate) fn days(&self) -> [&Vec<TimeSlot>; 7] {
+        [&self.monday, &self.tuesday, &self.wednesday, &self.thursday, &self.friday, &self.saturday, &self.sunday]
+    }
+
+    pub(crate) fn from_days(days: [Vec<TimeSlot>; 7]) -> Self {
+        let [monday, tuesday, wednesday, thursday, friday, saturday, sunday] = days;
+        Self { monday, tuesday, wednesday, thursday, friday, saturday, sunday }
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
 pub struct Break {
-    start_time: f64,
-    end_time: f64,
+    start_time: TimeOfDay,
+    end_time: TimeOfDay,
     is_regular: bool,
     date: Option<u64>,
 }
@@ -93,33 +198,168 @@ pub struct AccountBreaks {
     one_time_breaks: Vec<Break>,
 }
 
+#[derive(BorshStorageKey, BorshSerialize)]
+pub(crate) enum StorageKey {
+    Tasks,
+    AccountTasks { account_hash: [u8; 32] },
+    Rewards,
+    AccountRewards { account_hash: [u8; 32] },
+    AccountRewardPoints,
+    NextTaskIds,
+    NextRewardIds,
+    CompletedTasksPerDay,
+    AccountCompletedTasksPerDay { account_hash: [u8; 32] },
+    WorkingHours,
+    TimeSlots,
+    Breaks,
+    StorageAccounts,
+    AccessGrants,
+    AccountAccessGrants { account_hash: [u8; 32] },
+    Projects,
+    AccountProjects { account_hash: [u8; 32] },
+    NextProjectIds,
+    Tags,
+    AccountTags { account_hash: [u8; 32] },
+    NextTagIds,
+    Workspaces,
+    WorkspaceMembers { account_hash: [u8; 32] },
+    Memberships,
+    NftTokens,
+    NftOwnerTokens,
+    AccountNftTokens { account_hash: [u8; 32] },
+    MilestonesReached,
+    PointsLedger,
+    AccountPointsLedger { account_hash: [u8; 32] },
+    RedemptionRequests,
+    AccountRedemptionRequests { account_hash: [u8; 32] },
+    NextRedemptionIds,
+    ApproverRequests,
+    SupervisedTasks,
+    UtcOffsets,
+    CompletedTasksPerMonth,
+    AccountCompletedTasksPerMonth { account_hash: [u8; 32] },
+    CompletedTasksTotal,
+}
+
 #[near_bindgen]
-#[derive(Default, BorshDeserialize, BorshSerialize)]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct TodoListV1 {
-    tasks: HashMap<AccountId, Vec<Task>>,
-    rewards: HashMap<AccountId, Vec<Reward>>,
-    account_reward_points: HashMap<AccountId, i64>,
-    completed_tasks_per_day: HashMap<AccountId, HashMap<String, u32>>,
-    working_hours: HashMap<AccountId, WeeklyWorkingHours>,
-    time_slots: HashMap<AccountId, TimeSlots>,
-    breaks: HashMap<AccountId, AccountBreaks>,
+    tasks: LookupMap<AccountId, UnorderedMap<u64, Task>>,
+    rewards: LookupMap<AccountId, UnorderedMap<u64, Reward>>,
+    account_reward_points: LookupMap<AccountId, i64>,
+    next_task_ids: LookupMap<AccountId, u64>,
+    next_reward_ids: LookupMap<AccountId, u64>,
+    /// Completions per local day, counted from the Unix epoch.
+    completed_tasks_per_day: LookupMap<AccountId, UnorderedMap<u32, u32>>,
+    working_hours: LookupMap<AccountId, WeeklyWorkingHours>,
+    time_slots: LookupMap<AccountId, TimeSlots>,
+    breaks: LookupMap<AccountId, AccountBreaks>,
+    storage_accounts: LookupMap<AccountId, storage::AccountStorage>,
+    access_grants: LookupMap<AccountId, UnorderedMap<AccountId, Vec<Permission>>>,
+    limits: ValidationLimits,
+    owner_id: AccountId,
+    projects: LookupMap<AccountId, UnorderedMap<u64, Label>>,
+    next_project_ids: LookupMap<AccountId, u64>,
+    tags: LookupMap<AccountId, UnorderedMap<u64, Label>>,
+    next_tag_ids: LookupMap<AccountId, u64>,
+    workspaces: LookupMap<AccountId, UnorderedMap<AccountId, Role>>,
+    /// Workspaces each account is a member of.
+    memberships: LookupMap<AccountId, Vec<AccountId>>,
+    /// Sum of all point balances, the NEP-141 total supply.
+    total_points: i64,
+    nft_tokens: UnorderedMap<String, nft::Token>,
+    nft_owner_tokens: LookupMap<AccountId, UnorderedSet<String>>,
+    next_nft_id: u64,
+    /// Highest entry of `nft::MILESTONES` each account has a badge for.
+    milestones_reached: LookupMap<AccountId, u32>,
+    points_ledger: LookupMap<AccountId, Vector<PointsLedgerEntry>>,
+    redemption_requests: LookupMap<AccountId, UnorderedMap<u64, RedemptionRequest>>,
+    next_redemption_ids: LookupMap<AccountId, u64>,
+    /// Requester and request ID of the pending requests each approver decides on.
+    approver_requests: LookupMap<AccountId, Vec<(AccountId, u64)>>,
+    /// Account and ID of the tasks each supervisor verifies.
+    supervised_tasks: LookupMap<AccountId, Vec<(AccountId, u64)>>,
+    /// Minutes from UTC of the account's time zone, which decides the day a
+    /// completion counts for.
+    utc_offsets: LookupMap<AccountId, i16>,
+    /// Completions of days rolled up by `roll_up_completed_days`, per month
+    /// index.
+    completed_tasks_per_month: LookupMap<AccountId, UnorderedMap<u32, u32>>,
+    /// Completions over the account's whole history, days and months alike,
+    /// kept so milestones need not add them all up.
+    completed_tasks_total: LookupMap<AccountId, u32>,
+}
+
+fn account_hash(account_id: &AccountId) -> [u8; 32] {
+    env::sha256_array(account_id.as_bytes())
 }
 
 #[near_bindgen]
 impl TodoListV1 {
+    /// The caller becomes the owner, who alone can `upgrade` the contract.
     #[init]
     pub fn new() -> Self {
-        Self::default()
+        migration::write_state_version();
+        Self {
+            tasks: LookupMap::new(StorageKey::Tasks),
+            rewards: LookupMap::new(StorageKey::Rewards),
+            account_reward_points: LookupMap::new(StorageKey::AccountRewardPoints),
+            next_task_ids: LookupMap::new(StorageKey::NextTaskIds),
+            next_reward_ids: LookupMap::new(StorageKey::NextRewardIds),
+            completed_tasks_per_day: LookupMap::new(StorageKey::CompletedTasksPerDay),
+            working_hours: LookupMap::new(StorageKey::WorkingHours),
+            time_slots: LookupMap::new(StorageKey::TimeSlots),
+            breaks: LookupMap::new(StorageKey::Breaks),
+            storage_accounts: LookupMap::new(StorageKey::StorageAccounts),
+            access_grants: LookupMap::new(StorageKey::AccessGrants),
+            limits: ValidationLimits::default(),
+            owner_id: env::predecessor_account_id(),
+            projects: LookupMap::new(StorageKey::Projects),
+            next_project_ids: LookupMap::new(StorageKey::NextProjectIds),
+            tags: LookupMap::new(StorageKey::Tags),
+            next_tag_ids: LookupMap::new(StorageKey::NextTagIds),
+            workspaces: LookupMap::new(StorageKey::Workspaces),
+            memberships: LookupMap::new(StorageKey::Memberships),
+            total_points: 0,
+            nft_tokens: UnorderedMap::new(StorageKey::NftTokens),
+            nft_owner_tokens: LookupMap::new(StorageKey::NftOwnerTokens),
+            next_nft_id: 1,
+            milestones_reached: LookupMap::new(StorageKey::MilestonesReached),
+            points_ledger: LookupMap::new(StorageKey::PointsLedger),
+            redemption_requests: LookupMap::new(StorageKey::RedemptionRequests),
+            next_redemption_ids: LookupMap::new(StorageKey::NextRedemptionIds),
+            approver_requests: LookupMap::new(StorageKey::ApproverRequests),
+            supervised_tasks: LookupMap::new(StorageKey::SupervisedTasks),
+            utc_offsets: LookupMap::new(StorageKey::UtcOffsets),
+            completed_tasks_per_month: LookupMap::new(StorageKey::CompletedTasksPerMonth),
+            completed_tasks_total: LookupMap::new(StorageKey::CompletedTasksTotal),
+        }
+    }
+
+    pub fn get_validation_limits(&self) -> ValidationLimits {
+        self.limits.clone()
+    }
+
+    #[private]
+    #[handle_result]
+    pub fn set_validation_limits(&mut self, limits: ValidationLimits) -> Result<(), ContractError> {
+        limits.validate()?;
+        self.limits = limits;
+        Ok(())
     }
 
-    pub fn add_break(&mut self, start_time: f64, end_time: f64, is_regular: bool, date: Option<u64>) {
-        let account_id = env::signer_account_id();
+    #[handle_result]
+    pub fn add_break(&mut self, start_time: TimeOfDay, end_time: TimeOfDay, is_regular: bool, date: Option<u64>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageSchedule)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        validation::validate_time_range(start_time, end_time)?;
         let new_break = Break {
             start_time,
             end_time,
             is_regular,
             date,
         };
+        TodoEvent::BreakAdded { account_id: &account_id, break_: &new_break }.emit();
         let account_breaks = self.breaks.entry(account_id.clone()).or_insert_with(|| AccountBreaks {
             regular_breaks: vec![],
             one_time_breaks: vec![],
@@ -130,188 +370,317 @@ impl TodoListV1 {
         } else {
             account_breaks.one_time_breaks.push(new_break);
         }
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
     }
 
     pub fn get_breaks(&self, account_id: AccountId) -> AccountBreaks {
-        self.breaks.get(&account_id).cloned().unwrap_or_else(|| AccountBreaks {
+        self.breaks.get(&account_id).cloned().unwrap_or(AccountBreaks {
             regular_breaks: vec![],
             one_time_breaks: vec![],
         })
     }
 
-    pub fn remove_break(&mut self, start_time: f64, end_time: f64, is_regular: bool, date: Option<u64>) {
-        let account_id = env::signer_account_id();
-        if let Some(account_breaks) = self.breaks.get_mut(&account_id) {
-            if is_regular {
-                account_breaks.regular_breaks.retain(|b| !(b.start_time == start_time && b.end_time == end_time));
-            } else {
-                account_breaks.one_time_breaks.retain(|b| !(b.start_time == start_time && b.end_time == end_time && b.date == date));
-            }
+    #[handle_result]
+    pub fn remove_break(&mut self, start_time: TimeOfDay, end_time: TimeOfDay, is_regular: bool, date: Option<u64>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageSchedule)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let account_breaks = self.breaks.get_mut(&account_id).ok_or(ContractError::BreakNotFound)?;
+        let breaks = if is_regular {
+            &mut account_breaks.regular_breaks
+        } else {
+            &mut account_breaks.one_time_breaks
+        };
+
+        let initial_len = breaks.len();
+        if is_regular {
+            breaks.retain(|b| !(b.start_time == start_time && b.end_time == end_time));
+        } else {
+            breaks.retain(|b| !(b.start_time == start_time && b.end_time == end_time && b.date == date));
         }
+        if breaks.len() == initial_len {
+            return Err(ContractError::BreakNotFound);
+        }
+        TodoEvent::BreakRemoved { account_id: &account_id, start_time, end_time, is_regular, date }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
     }
 
-    pub fn update_break(&mut self, old_start_time: f64, old_end_time: f64, new_start_time: f64, new_end_time: f64, is_regular: bool, new_date: Option<u64>) {
-        let account_id = env::signer_account_id();
-        if let Some(account_breaks) = self.breaks.get_mut(&account_id) {
-            if is_regular {
-                if let Some(break_) = account_breaks.regular_breaks.iter_mut().find(|b| b.start_time == old_start_time && b.end_time == old_end_time) {
-                    break_.start_time = new_start_time;
-                    break_.end_time = new_end_time;
-                }
-            } else {
-                if let Some(break_) = account_breaks.one_time_breaks.iter_mut().find(|b| b.start_time == old_start_time && b.end_time == old_end_time) {
-                    break_.start_time = new_start_time;
-                    break_.end_time = new_end_time;
-                    break_.date = new_date;
-                }
-            }
+    #[handle_result]
+    pub fn update_break(&mut self, old_start_time: TimeOfDay, old_end_time: TimeOfDay, new_start_time: TimeOfDay, new_end_time: TimeOfDay, is_regular: bool, new_date: Option<u64>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageSchedule)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        validation::validate_time_range(new_start_time, new_end_time)?;
+        let account_breaks = self.breaks.get_mut(&account_id).ok_or(ContractError::BreakNotFound)?;
+        let breaks = if is_regular {
+            &mut account_breaks.regular_breaks
+        } else {
+            &mut account_breaks.one_time_breaks
+        };
+
+        let break_ = breaks
+            .iter_mut()
+            .find(|b| b.start_time == old_start_time && b.end_time == old_end_time)
+            .ok_or(ContractError::BreakNotFound)?;
+        break_.start_time = new_start_time;
+        break_.end_time = new_end_time;
+        if !is_regular {
+            break_.date = new_date;
         }
+        TodoEvent::BreakUpdated { account_id: &account_id, break_ }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
     }
 
+    #[handle_result]
     pub fn add_task(&mut self, title: String, description: String, priority: u8, 
-                    deadline: Option<u64>, estimated_time: Option<f64>, reward_points: i64) {
-        let account_id = env::signer_account_id();
-        
-
-        let task = Task {
-            id: self.tasks.get(&account_id).map_or(1, |tasks| tasks.len() as u64 + 1),
-            title,
-            description,
-            priority,
-            deadline,
-            estimated_time,
-            reward_points,
-            completed: false,
-            owner: AccountIdWrapper(account_id.clone()),
-        };
-        self.tasks.entry(account_id).or_insert_with(Vec::new).push(task);
+                    deadline: Option<u64>, estimated_time: Option<DurationMinutes>, reward_points: i64, account_id: Option<AccountId>) -> Result<u64, ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let id = self.create_task(&account_id, title, description, priority, deadline, estimated_time, reward_points, None)?;
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(id)
     }
 
-    pub fn remove_task(&mut self, id: u64) {
-        let account_id = env::signer_account_id();
-        
+    #[handle_result]
+    pub fn remove_task(&mut self, id: u64, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
 
-        if let Some(tasks) = self.tasks.get_mut(&account_id) {
-            tasks.retain(|task| task.id != id);
-        }
+        self.delete_task(&account_id, id)?;
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
     }
 
     pub fn get_tasks(&self, account_id: AccountId) -> Vec<Task> {
-        
-        self.tasks.get(&account_id).cloned().unwrap_or_default()
+        self.tasks
+            .get(&account_id)
+            .map(|tasks| tasks.values().cloned().collect())
+            .unwrap_or_default()
     }
 
+    #[handle_result]
     pub fn update_task(&mut self, id: u64, title: String, description: String, priority: u8, 
-                       deadline: Option<u64>, estimated_time: Option<f64>, reward_points: i64) {
-        let account_id = env::signer_account_id();
-        
+                       deadline: Option<u64>, estimated_time: Option<DurationMinutes>, reward_points: i64, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        self.limits.validate_task(&title, &description, priority, estimated_time, reward_points)?;
+
+        let task = self.task_mut(&account_id, id)?;
+        // Only the supervisor decides what a supervised task is worth.
+        if task.supervisor.as_ref().is_some_and(|supervisor| supervisor.0 != env::predecessor_account_id()) {
+            return Err(ContractError::Unauthorized);
+        }
+        // The points of a completed task were already credited.
+        if task.completed && task.reward_points != reward_points {
+            return Err(ContractError::AlreadyCompleted(id));
+        }
+        task.title = title;
+        task.description = description;
+        task.priority = priority;
+        task.deadline = deadline;
+        task.estimated_time = estimated_time;
+        task.reward_points = reward_points;
+        TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
 
-        if let Some(tasks) = self.tasks.get_mut(&account_id) {
-            if let Some(task) = tasks.iter_mut().find(|task| task.id == id) {
-                task.title = title;
-                task.description = description;
-                task.priority = priority;
-                task.deadline = deadline;
-                task.estimated_time = estimated_time;
-                task.reward_points = reward_points;
-            }
+    /// Completes a task and credits its points. For a recurring task, returns
+    /// the ID of the next occurrence if the series continues.
+    #[handle_result]
+    pub fn mark_complete(&mut self, id: u64, account_id: Option<AccountId>) -> Result<Option<u64>, ContractError> {
+        let account_id = self.authorize(account_id, Permission::CompleteTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        if self.get_task(account_id.clone(), id).is_some_and(|task| task.supervisor.is_some()) {
+            return Err(ContractError::VerificationRequired(id));
         }
+        let next_id = self.complete_task(&account_id, id)?;
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(next_id)
     }
 
-    pub fn mark_complete(&mut self, id: u64) {
-        let account_id = env::signer_account_id();
-        
+    /// Reopens a completed task, taking back the points credited for it and
+    /// removing it from the stats of the day it was completed. Fails if the
+    /// account credited no longer holds those points. Verified supervised tasks
+    /// stay closed.
+    #[handle_result]
+    pub fn mark_incomplete(&mut self, id: u64, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::CompleteTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        let task = self.get_task(account_id.clone(), id).ok_or(ContractError::TaskNotFound(id))?;
+        if !task.completed {
+            return Err(ContractError::NotCompleted(id));
+        }
+        if task.supervisor.is_some() {
+            return Err(ContractError::VerifiedTask(id));
+        }
+        // Tasks completed before credits were recorded take back what they
+        // would be credited now.
+        let (recipient, reward_points) = match task.credit {
+            Some((recipient, amount)) => (recipient.0, amount),
+            None => (task.points_recipient(), task.reward_points),
+        };
+        let owner = task.owner;
+        let available = self.get_account_reward_points(recipient.clone());
+        if available < reward_points {
+            return Err(ContractError::InsufficientPoints { required: reward_points, available });
+        }
 
-        if let Some(tasks) = self.tasks.get_mut(&account_id) {
-            if let Some(task) = tasks.iter_mut().find(|task| task.id == id) {
-                task.completed = true;
-                let reward_points = task.reward_points;
-                let account_points = self.account_reward_points.entry(account_id.clone()).or_insert(0);
-                *account_points = (*account_points + reward_points).max(0);
-                self.update_completed_tasks_per_day(&account_id);
-            }
+        let task = self.task_mut(&account_id, id)?;
+        task.completed = false;
+        task.completed_at = None;
+        task.credit = None;
+        let completed_day = task.completed_day.take();
+        let change = PointsChange::TaskReopened { owner, task_id: id };
+        TodoEvent::TaskReopened { account_id: &account_id, task_id: id, reward_points }.emit();
+        self.change_points(&recipient, -reward_points, change)?;
+        // Tasks completed before completion days were recorded have no day to undo.
+        if let Some(day) = completed_day {
+            self.update_completed_tasks_per_day(&account_id, day, -1);
         }
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
     }
 
-    pub fn add_reward(&mut self, title: String, description: String, cost: i64) {
-        let account_id = env::signer_account_id();
+    #[handle_result]
+    pub fn add_reward(&mut self, title: String, description: String, cost: i64, account_id: Option<AccountId>) -> Result<u64, ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageRewards)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        self.limits.validate_reward(&title, &description, cost)?;
+        let id = Self::take_next_id(&mut self.next_reward_ids, &account_id);
 
         let reward = Reward {
-            id: self.rewards.get(&account_id).map_or(1, |rewards| rewards.len() as u64 + 1),
+            id,
             title,
             description,
             cost,
+            limits: RewardLimits::default(),
+            active: true,
+            redeemed: 0,
+            recent_redemptions: vec![],
+            approver: None,
         };
-        self.rewards.entry(account_id).or_insert_with(Vec::new).push(reward);
+        TodoEvent::RewardCreated { account_id: &account_id, reward: &reward }.emit();
+        self.account_rewards_mut(&account_id).insert(id, reward);
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(id)
     }
 
     pub fn get_rewards(&self, account_id: AccountId) -> Vec<Reward> {
-        
-        self.rewards.get(&account_id).cloned().unwrap_or_default()
+        self.rewards
+            .get(&account_id)
+            .map(|rewards| rewards.values().cloned().collect())
+            .unwrap_or_default()
     }
 
-    pub fn remove_reward(&mut self, id: u64) {
-        let account_id = env::signer_account_id();
-        
-
-        if let Some(rewards) = self.rewards.get_mut(&account_id) {
-            rewards.retain(|reward| reward.id != id);
-        }
+    #[handle_result]
+    pub fn remove_reward(&mut self, id: u64, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageRewards)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        self.rewards
+            .get_mut(&account_id)
+            .and_then(|rewards| rewards.remove(&id))
+            .ok_or(ContractError::RewardNotFound(id))?;
+        TodoEvent::RewardRemoved { account_id: &account_id, reward_id: id }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
     }
 
-    pub fn redeem_reward(&mut self, id: u64) -> bool {
-        let account_id = env::signer_account_id();
-        
-
-        if let Some(rewards) = self.rewards.get(&account_id) {
-            if let Some(reward) = rewards.iter().find(|reward| reward.id == id) {
-                let account_points = self.account_reward_points.entry(account_id.clone()).or_insert(0);
-                if *account_points >= reward.cost {
-                    *account_points -= reward.cost;
-                    return true;
-                }
-            }
+    /// Spends the reward's cost and mints a badge recording the redemption,
+    /// returning the badge's token ID.
+    #[handle_result]
+    pub fn redeem_reward(&mut self, id: u64, account_id: Option<AccountId>) -> Result<String, ContractError> {
+        let account_id = self.authorize(account_id, Permission::RedeemRewards)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        let now = env::block_timestamp();
+        let reward = self.get_reward(account_id.clone(), id).ok_or(ContractError::RewardNotFound(id))?;
+        if reward.approver.is_some() {
+            return Err(ContractError::ApprovalRequired(id));
+        }
+        self.check_reward_available(&account_id, &reward, now)?;
+        let cost = reward.cost;
+        let available = self.get_account_reward_points(account_id.clone());
+        if available < cost {
+            return Err(ContractError::InsufficientPoints { required: cost, available });
         }
-        false
+        self.reward_mut(&account_id, id)?.record_redemption(now);
+        TodoEvent::RewardRedeemed { account_id: &account_id, reward_id: id, cost }.emit();
+        self.change_points(&account_id, -cost, PointsChange::RewardRedeemed { reward_id: id })?;
+        let badge_owner_id = self.redemption_badge_owner(&account_id, env::predecessor_account_id());
+        let token_id = self.mint_reward_badge(&badge_owner_id, &reward);
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(token_id)
     }
 
     pub fn get_account_reward_points(&self, account_id: AccountId) -> i64 {
-        
         *self.account_reward_points.get(&account_id).unwrap_or(&0)
     }
 
-    fn update_completed_tasks_per_day(&mut self, account_id: &AccountId) {
-
-        let today = env::block_timestamp() / 86400000000000;
-        let date_string = format!("{}", today);
-
-        let user_tasks = self.completed_tasks_per_day.entry(account_id.clone()).or_insert_with(HashMap::new);
-        let count = user_tasks.entry(date_string).or_insert(0);
-        *count += 1;
+    /// Counts a completion, or takes one back, on the account's local `day`.
+    /// Completions taken back from a day that was rolled up come off its
+    /// month instead.
+    fn update_completed_tasks_per_day(&mut self, account_id: &AccountId, day: u32, delta: i32) {
+        if delta < 0 && !self.completed_tasks_per_day.get(account_id).is_some_and(|days| days.contains_key(&day)) {
+            let month = time::month_index(day);
+            let Some(months) = self.completed_tasks_per_month.get_mut(account_id) else {
+                return;
+            };
+            let Some(count) = months.get_mut(&month) else {
+                return;
+            };
+            *count = count.saturating_add_signed(delta);
+            if *count == 0 {
+                months.remove(&month);
+            }
+        } else {
+            let user_tasks = self.account_completed_tasks_per_day_mut(account_id);
+            let count = user_tasks.entry(day).or_insert(0);
+            *count = count.saturating_add_signed(delta);
+            if *count == 0 {
+                user_tasks.remove(&day);
+            }
+        }
+        self.add_to_completed_total(account_id, delta);
     }
 
-    pub fn get_completed_tasks_per_day(&self, account_id: AccountId) -> HashMap<String, u32> {
-        
-        self.completed_tasks_per_day.get(&account_id).cloned().unwrap_or_default()
+    /// Completions per day not rolled up yet, keyed by the local day counted
+    /// from the Unix epoch.
+    pub fn get_completed_tasks_per_day(&self, account_id: AccountId) -> HashMap<u32, u32> {
+        self.completed_tasks_per_day
+            .get(&account_id)
+            .map(|days| days.iter().map(|(day, count)| (*day, *count)).collect())
+            .unwrap_or_default()
     }
 
     pub fn get_working_hours(&self, account_id: AccountId) -> WeeklyWorkingHours {
         
-        self.working_hours.get(&account_id).cloned().unwrap_or_else(|| WeeklyWorkingHours {
-            monday: WorkingHours { start_time: 9.0, end_time: 16.0 },
-            tuesday: WorkingHours { start_time: 9.0, end_time: 16.0 },
-            wednesday: WorkingHours { start_time: 9.0, end_time: 16.0 },
-            thursday: WorkingHours { start_time: 9.0, end_time: 16.0 },
-            friday: WorkingHours { start_time: 9.0, end_time: 16.0 },
-            saturday: WorkingHours { start_time: 9.0, end_time: 16.0 },
-            sunday: WorkingHours { start_time: 9.0, end_time: 16.0 },
+        const DEFAULT_HOURS: WorkingHours = WorkingHours { start_time: TimeOfDay::from_hm(9, 0), end_time: TimeOfDay::from_hm(16, 0) };
+        self.working_hours.get(&account_id).cloned().unwrap_or(WeeklyWorkingHours {
+            monday: DEFAULT_HOURS,
+            tuesday: DEFAULT_HOURS,
+            wednesday: DEFAULT_HOURS,
+            thursday: DEFAULT_HOURS,
+            friday: DEFAULT_HOURS,
+            saturday: DEFAULT_HOURS,
+            sunday: DEFAULT_HOURS,
         })
     }
 
-    pub fn update_working_hours(&mut self, working_hours: WeeklyWorkingHours) {
-        let account_id = env::signer_account_id();
-        
-        self.working_hours.insert(account_id, working_hours);
+    #[handle_result]
+    pub fn update_working_hours(&mut self, working_hours: WeeklyWorkingHours, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageSchedule)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        validation::validate_working_hours(&working_hours)?;
+
+        TodoEvent::WorkingHoursUpdated { account_id: &account_id, working_hours: &working_hours }.emit();
+        self.working_hours.insert(account_id.clone(), working_hours);
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
     }
 
     pub fn get_time_slots(&self, account_id: AccountId) -> Option<TimeSlots> {
@@ -319,19 +688,183 @@ impl TodoListV1 {
         self.time_slots.get(&account_id).cloned()
     }
 
-    pub fn update_time_slots(&mut self, time_slots: TimeSlots) {
-        let account_id = env::signer_account_id();
-        
-        self.time_slots.insert(account_id, time_slots);
+    #[handle_result]
+    pub fn update_time_slots(&mut self, time_slots: TimeSlots, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageSchedule)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        validation::validate_time_slots(&time_slots)?;
+
+        TodoEvent::TimeSlotsUpdated { account_id: &account_id, time_slots: &time_slots }.emit();
+        self.time_slots.insert(account_id.clone(), time_slots);
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+}
+
+impl TodoListV1 {
+    /// Hands out the next ID from a per-account sequence. IDs start at 1 and are
+    /// never reused, even after the entry holding them is removed.
+    fn take_next_id(sequences: &mut LookupMap<AccountId, u64>, account_id: &AccountId) -> u64 {
+        let next_id = sequences.entry(account_id.clone()).or_insert(1);
+        let id = *next_id;
+        *next_id += 1;
+        id
+    }
+
+    pub(crate) fn create_task(&mut self, account_id: &AccountId, title: String, description: String, priority: u8, deadline: Option<u64>,
+                              estimated_time: Option<DurationMinutes>, reward_points: i64, supervisor: Option<AccountId>) -> Result<u64, ContractError> {
+        self.limits.validate_task(&title, &description, priority, estimated_time, reward_points)?;
+        let task_count = self.tasks.get(account_id).map_or(0, |tasks| tasks.len());
+        self.limits.validate_task_count(task_count)?;
+        let id = Self::take_next_id(&mut self.next_task_ids, account_id);
+
+        let task = Task {
+            id,
+            title,
+            description,
+            priority,
+            deadline,
+            estimated_time,
+            reward_points,
+            completed: false,
+            completed_at: None,
+            completed_day: None,
+            credit: None,
+            actual_time: None,
+            recurrence: None,
+            next_occurrence: None,
+            parent_id: None,
+            depends_on: vec![],
+            project_id: None,
+            tag_ids: vec![],
+            assignee: None,
+            supervisor: supervisor.map(AccountIdWrapper),
+            submission: None,
+            owner: AccountIdWrapper(account_id.clone()),
+        };
+        if let Some(supervisor) = &task.supervisor {
+            self.index_supervised_task(&supervisor.0, account_id, id);
+        }
+        TodoEvent::TaskCreated { account_id, task: &task }.emit();
+        self.account_tasks_mut(account_id).insert(id, task);
+        Ok(id)
+    }
+
+    /// Removes the task along with the references other tasks and its
+    /// supervisor hold to it.
+    pub(crate) fn delete_task(&mut self, account_id: &AccountId, id: u64) -> Result<(), ContractError> {
+        let task = self
+            .tasks
+            .get_mut(account_id)
+            .and_then(|tasks| tasks.remove(&id))
+            .ok_or(ContractError::TaskNotFound(id))?;
+        if let Some(supervisor) = &task.supervisor {
+            self.unindex_supervised_task(&supervisor.0, account_id, id);
+        }
+        self.unlink_task(account_id, id);
+        TodoEvent::TaskRemoved { account_id, task_id: id }.emit();
+        Ok(())
+    }
+
+    /// Completes an open task whose dependencies are done and credits its
+    /// points. Returns the ID of the next occurrence of a recurring task.
+    pub(crate) fn complete_task(&mut self, account_id: &AccountId, id: u64) -> Result<Option<u64>, ContractError> {
+        let task = self.get_task(account_id.clone(), id).ok_or(ContractError::TaskNotFound(id))?;
+        if task.completed {
+            return Err(ContractError::AlreadyCompleted(id));
+        }
+        let pending = self.pending_dependencies(account_id, &task);
+        if !pending.is_empty() {
+            return Err(ContractError::DependenciesIncomplete(pending));
+        }
+        let completed_at = env::block_timestamp();
+        let completed_day = self.local_day(account_id, completed_at);
+        let task = self.task_mut(account_id, id)?;
+        task.completed = true;
+        task.completed_at = Some(completed_at);
+        task.completed_day = Some(completed_day);
+        task.credit = Some((AccountIdWrapper(task.points_recipient()), task.reward_points));
+        task.submission = None;
+        let task = task.clone();
+        if let Some(supervisor) = &task.supervisor {
+            self.unindex_supervised_task(&supervisor.0, account_id, id);
+        }
+        TodoEvent::TaskCompleted { account_id, task_id: id, reward_points: task.reward_points }.emit();
+        let change = PointsChange::TaskCompleted { owner: task.owner.clone(), task_id: id };
+        self.change_points(&task.points_recipient(), task.reward_points, change)?;
+        self.update_completed_tasks_per_day(account_id, completed_day, 1);
+        self.award_milestones(account_id, &task.points_recipient());
+        self.spawn_next_occurrence(account_id, &task)
+    }
+
+    /// Mints or burns `delta` points for the account, never going below zero,
+    /// and returns the new balance.
+    pub(crate) fn change_points(&mut self, account_id: &AccountId, delta: i64, change: PointsChange) -> Result<i64, ContractError> {
+        let (old_balance, new_balance) = self.move_points(account_id, delta, change)?;
+        let minted = new_balance - old_balance;
+        self.total_points = self.total_points.checked_add(minted).ok_or(ContractError::PointsOverflow)?;
+        let entry = [FtMint { owner_id: account_id, amount: U128(minted.unsigned_abs().into()), memo: None }];
+        match minted.cmp(&0) {
+            Ordering::Greater => FtEvent::FtMint(entry).emit(),
+            Ordering::Less => FtEvent::FtBurn(entry).emit(),
+            Ordering::Equal => {}
+        }
+        Ok(new_balance)
+    }
+
+    /// Applies `delta` to the account's balance, never going below zero,
+    /// without minting or burning, and records it in the account's ledger.
+    /// Returns the old and new balance.
+    pub(crate) fn move_points(&mut self, account_id: &AccountId, delta: i64, change: PointsChange) -> Result<(i64, i64), ContractError> {
+        let account_points = self.account_reward_points.entry(account_id.clone()).or_insert(0);
+        let old_balance = *account_points;
+        *account_points = old_balance.checked_add(delta).ok_or(ContractError::PointsOverflow)?.max(0);
+        let new_balance = *account_points;
+        TodoEvent::PointsChanged { account_id, old_balance, new_balance }.emit();
+        if new_balance != old_balance {
+            self.record_points_change(account_id, new_balance - old_balance, new_balance, change);
+        }
+        Ok((old_balance, new_balance))
+    }
+
+    pub(crate) fn task_mut(&mut self, account_id: &AccountId, id: u64) -> Result<&mut Task, ContractError> {
+        self.tasks
+            .get_mut(account_id)
+            .and_then(|tasks| tasks.get_mut(&id))
+            .ok_or(ContractError::TaskNotFound(id))
+    }
+
+    pub(crate) fn account_tasks_mut(&mut self, account_id: &AccountId) -> &mut UnorderedMap<u64, Task> {
+        self.tasks.entry(account_id.clone()).or_insert_with(|| {
+            UnorderedMap::new(StorageKey::AccountTasks { account_hash: account_hash(account_id) })
+        })
+    }
+
+    pub(crate) fn account_rewards_mut(&mut self, account_id: &AccountId) -> &mut UnorderedMap<u64, Reward> {
+        self.rewards.entry(account_id.clone()).or_insert_with(|| {
+            UnorderedMap::new(StorageKey::AccountRewards { account_hash: account_hash(account_id) })
+        })
+    }
+
+    pub(crate) fn account_completed_tasks_per_day_mut(&mut self, account_id: &AccountId) -> &mut UnorderedMap<u32, u32> {
+        self.completed_tasks_per_day.entry(account_id.clone()).or_insert_with(|| {
+            UnorderedMap::new(StorageKey::AccountCompletedTasksPerDay { account_hash: account_hash(account_id) })
+        })
+    }
+
+    pub(crate) fn account_completed_tasks_per_month_mut(&mut self, account_id: &AccountId) -> &mut UnorderedMap<u32, u32> {
+        self.completed_tasks_per_month.entry(account_id.clone()).or_insert_with(|| {
+            UnorderedMap::new(StorageKey::AccountCompletedTasksPerMonth { account_hash: account_hash(account_id) })
+        })
     }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::testing_env;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, ONE_NEAR};
 
-    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+    pub(crate) fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
         builder
             .current_account_id(accounts(0))
@@ -340,20 +873,32 @@ mod tests {
         builder
     }
 
+    /// Shorthand for a time on the hour.
+    pub(crate) fn at(hours: u16) -> TimeOfDay {
+        TimeOfDay::from_hm(hours, 0)
+    }
+
+    pub(crate) fn setup_contract(account_id: AccountId) -> TodoListV1 {
+        testing_env!(get_context(account_id.clone()).attached_deposit(ONE_NEAR).build());
+        let mut contract = TodoListV1::new();
+        contract.storage_deposit(None, None);
+        testing_env!(get_context(account_id).build());
+        contract
+    }
+
     #[test]
     fn test_add_task() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
         
         contract.add_task(
             "Test Task".to_string(),
             "Task Description".to_string(),
             1,
             Some(1_640_995_200_000_000_000),
-            Some(2.0),
+            Some(DurationMinutes::from_hm(2, 0)),
             10,
-        );
+            None,
+        ).unwrap();
         
         let tasks = contract.get_tasks(accounts(1));
         assert_eq!(tasks.len(), 1);
@@ -362,24 +907,23 @@ mod tests {
         assert_eq!(task.description, "Task Description");
         assert_eq!(task.priority, 1);
         assert_eq!(task.deadline, Some(1_640_995_200_000_000_000));
-        assert_eq!(task.estimated_time, Some(2.0));
+        assert_eq!(task.estimated_time, Some(DurationMinutes::from_hm(2, 0)));
         assert_eq!(task.reward_points, 10);
     }
 
     #[test]
     fn test_update_task() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
         contract.add_task(
             "Test Task".to_string(),
             "Task Description".to_string(),
             1,
             Some(1_640_995_200_000_000_000),
-            Some(2.0),
+            Some(DurationMinutes::from_hm(2, 0)),
             10,
-        );
+            None,
+        ).unwrap();
 
         let tasks = contract.get_tasks(accounts(1));
         let task_id = tasks[0].id;
@@ -390,9 +934,10 @@ mod tests {
             "Updated Description".to_string(),
             2,
             Some(1_641_995_200_000_000_000),
-            Some(3.0),
+            Some(DurationMinutes::from_hm(3, 0)),
             20,
-        );
+            None,
+        ).unwrap();
 
         let tasks = contract.get_tasks(accounts(1));
         assert_eq!(tasks.len(), 1);
@@ -401,40 +946,54 @@ mod tests {
         assert_eq!(task.description, "Updated Description");
         assert_eq!(task.priority, 2);
         assert_eq!(task.deadline, Some(1_641_995_200_000_000_000));
-        assert_eq!(task.estimated_time, Some(3.0));
+        assert_eq!(task.estimated_time, Some(DurationMinutes::from_hm(3, 0)));
         assert_eq!(task.reward_points, 20);
     }
 
     #[test]
     fn test_remove_task() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
         contract.add_task(
             "Test Task".to_string(),
             "Task Description".to_string(),
             1,
             Some(1_640_995_200_000_000_000),
-            Some(2.0),
+            Some(DurationMinutes::from_hm(2, 0)),
             10,
-        );
+            None,
+        ).unwrap();
 
         let tasks = contract.get_tasks(accounts(1));
         let task_id = tasks[0].id;
 
-        contract.remove_task(task_id);
+        contract.remove_task(task_id, None).unwrap();
         let tasks = contract.get_tasks(accounts(1));
         assert_eq!(tasks.len(), 0);
     }
 
+    #[test]
+    fn test_task_ids_not_reused_after_removal() {
+        let mut contract = setup_contract(accounts(1));
+
+        let first_id = contract.add_task("First".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        let second_id = contract.add_task("Second".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        contract.remove_task(first_id, None).unwrap();
+        let third_id = contract.add_task("Third".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+
+        assert_eq!((first_id, second_id, third_id), (1, 2, 3));
+        contract.mark_complete(second_id, None).unwrap();
+        let tasks = contract.get_tasks(accounts(1));
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.iter().find(|task| task.id == second_id).unwrap().completed);
+        assert!(!tasks.iter().find(|task| task.id == third_id).unwrap().completed);
+    }
+
     #[test]
     fn test_add_reward() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50);
+        contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50, None).unwrap();
 
         let rewards = contract.get_rewards(accounts(1));
         assert_eq!(rewards.len(), 1);
@@ -446,79 +1005,209 @@ mod tests {
 
     #[test]
     fn test_remove_reward() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50);
+        contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50, None).unwrap();
 
         let rewards = contract.get_rewards(accounts(1));
         let reward_id = rewards[0].id;
 
-        contract.remove_reward(reward_id);
+        contract.remove_reward(reward_id, None).unwrap();
         let rewards = contract.get_rewards(accounts(1));
         assert_eq!(rewards.len(), 0);
+
+        let new_reward_id = contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50, None).unwrap();
+        assert_eq!(new_reward_id, reward_id + 1);
     }
 
     #[test]
     fn test_redeem_reward() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50);
+        contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50, None).unwrap();
 
         let rewards = contract.get_rewards(accounts(1));
         let reward_id = rewards[0].id;
 
         contract.account_reward_points.insert(accounts(1), 100);
 
-        let result = contract.redeem_reward(reward_id);
-        assert!(result);
+        contract.redeem_reward(reward_id, None).unwrap();
         let points = contract.get_account_reward_points(accounts(1));
         assert_eq!(points, 50);
     }
 
+    #[test]
+    fn test_redeem_reward_errors() {
+        let mut contract = setup_contract(accounts(1));
+
+        let reward_id = contract.add_reward("Reward".to_string(), "Reward Description".to_string(), 50, None).unwrap();
+        contract.account_reward_points.insert(accounts(1), 30);
+
+        assert_eq!(
+            contract.redeem_reward(reward_id, None),
+            Err(ContractError::InsufficientPoints { required: 50, available: 30 })
+        );
+        assert_eq!(contract.redeem_reward(reward_id + 1, None), Err(ContractError::RewardNotFound(reward_id + 1)));
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 30);
+    }
+
+    #[test]
+    fn test_missing_task_errors() {
+        let mut contract = setup_contract(accounts(1));
+
+        assert_eq!(contract.remove_task(7, None), Err(ContractError::TaskNotFound(7)));
+        assert_eq!(contract.mark_complete(7, None), Err(ContractError::TaskNotFound(7)));
+        assert_eq!(
+            contract.update_task(7, "Title".to_string(), "".to_string(), 1, None, None, 10, None),
+            Err(ContractError::TaskNotFound(7))
+        );
+        assert_eq!(contract.remove_reward(7, None), Err(ContractError::RewardNotFound(7)));
+    }
+
+    #[test]
+    fn test_add_task_rejects_invalid_input() {
+        let mut contract = setup_contract(accounts(1));
+
+        assert_eq!(
+            contract.add_task("Test Task".to_string(), "".to_string(), 0, None, None, 10, None),
+            Err(ContractError::PriorityOutOfRange { priority: 0, min: 1, max: 5 })
+        );
+        assert_eq!(
+            contract.add_reward("Reward".to_string(), "".to_string(), -5, None),
+            Err(ContractError::NegativeValue { field: "cost", value: -5 })
+        );
+        assert!(contract.get_tasks(accounts(1)).is_empty());
+        assert!(contract.get_rewards(accounts(1)).is_empty());
+    }
+
+    #[test]
+    fn test_task_limit_per_account() {
+        let mut contract = setup_contract(accounts(1));
+
+        contract
+            .set_validation_limits(ValidationLimits { max_tasks_per_account: 1, ..ValidationLimits::default() })
+            .unwrap();
+        contract.add_task("First".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        assert_eq!(
+            contract.add_task("Second".to_string(), "".to_string(), 1, None, None, 10, None),
+            Err(ContractError::TaskLimitReached { max: 1 })
+        );
+    }
+
+    #[test]
+    fn test_mark_complete_twice() {
+        let mut contract = setup_contract(accounts(1));
+
+        let task_id = contract.add_task("Test Task".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        contract.mark_complete(task_id, None).unwrap();
+
+        assert_eq!(contract.mark_complete(task_id, None), Err(ContractError::AlreadyCompleted(task_id)));
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 10);
+    }
+
+    #[test]
+    fn test_mark_incomplete() {
+        let mut contract = setup_contract(accounts(1));
+        let first = contract.add_task("First".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        let second = contract.add_task("Second".to_string(), "".to_string(), 1, None, None, 30, None).unwrap();
+        assert_eq!(contract.mark_incomplete(first, None), Err(ContractError::NotCompleted(first)));
+
+        testing_env!(get_context(accounts(1)).block_timestamp(1_640_995_200_000_000_000).build());
+        contract.mark_complete(first, None).unwrap();
+        contract.mark_complete(second, None).unwrap();
+        let reward_id = contract.add_reward("Reward".to_string(), "".to_string(), 35, None).unwrap();
+        contract.redeem_reward(reward_id, None).unwrap();
+
+        // Reopened the next day, the completion still comes off the day it happened.
+        testing_env!(get_context(accounts(1)).block_timestamp(1_641_081_600_000_000_000).build());
+        assert_eq!(contract.mark_incomplete(first, None), Err(ContractError::InsufficientPoints { required: 10, available: 5 }));
+        contract.adjust_points(accounts(1), 5, "Top up".to_string()).unwrap();
+        contract.mark_incomplete(first, None).unwrap();
+        assert_eq!(contract.get_completed_tasks_per_day(accounts(1)), HashMap::from([(18993, 1)]));
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 0);
+        let task = contract.get_task(accounts(1), first).unwrap();
+        assert!(!task.completed);
+        assert_eq!(task.completed_at, None);
+    }
+
+    #[test]
+    fn test_reopen_takes_back_what_was_credited() {
+        let mut contract = setup_contract(accounts(1));
+        let id = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        contract.mark_complete(id, None).unwrap();
+        assert_eq!(
+            contract.update_task(id, "Task".to_string(), "".to_string(), 1, None, None, 50, None),
+            Err(ContractError::AlreadyCompleted(id))
+        );
+        contract.update_task(id, "Renamed".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        contract.adjust_points(accounts(1), 40, "Bonus".to_string()).unwrap();
+
+        contract.mark_incomplete(id, None).unwrap();
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 40);
+        assert_eq!(contract.get_task(accounts(1), id).unwrap().credit, None);
+    }
+
+    #[test]
+    fn test_mark_complete_emits_events() {
+        let mut contract = setup_contract(accounts(1));
+
+        let task_id = contract.add_task("Test Task".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        contract.mark_complete(task_id, None).unwrap();
+
+        let logs = get_logs();
+        assert_eq!(logs.len(), 4);
+        assert!(logs[0].starts_with(r#"EVENT_JSON:{"standard":"todolist","version":"1.0.0","event":"task_created""#));
+        assert_eq!(
+            logs[1],
+            r#"EVENT_JSON:{"standard":"todolist","version":"1.0.0","event":"task_completed","data":{"account_id":"bob","task_id":1,"reward_points":10}}"#
+        );
+        assert_eq!(
+            logs[2],
+            r#"EVENT_JSON:{"standard":"todolist","version":"1.0.0","event":"points_changed","data":{"account_id":"bob","old_balance":0,"new_balance":10}}"#
+        );
+        assert_eq!(
+            logs[3],
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"bob","amount":"10"}]}"#
+        );
+    }
+
     #[test]
     fn test_update_completed_tasks_per_day() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
         contract.add_task(
             "Test Task".to_string(),
             "Task Description".to_string(),
             1,
             Some(1_640_995_200_000_000_000),
-            Some(2.0),
+            Some(DurationMinutes::from_hm(2, 0)),
             10,
-        );
+            None,
+        ).unwrap();
 
         let tasks = contract.get_tasks(accounts(1));
         let task_id = tasks[0].id;
 
-        contract.mark_complete(task_id);
+        contract.mark_complete(task_id, None).unwrap();
         let completed_tasks = contract.get_completed_tasks_per_day(accounts(1));
         assert_eq!(completed_tasks.len(), 1);
     }
 
     #[test]
     fn test_update_working_hours() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
         let working_hours = WeeklyWorkingHours {
-            monday: WorkingHours { start_time: 9.0, end_time: 17.0 },
-            tuesday: WorkingHours { start_time: 9.0, end_time: 17.0 },
-            wednesday: WorkingHours { start_time: 9.0, end_time: 17.0 },
-            thursday: WorkingHours { start_time: 9.0, end_time: 17.0 },
-            friday: WorkingHours { start_time: 9.0, end_time: 17.0 },
-            saturday: WorkingHours { start_time: 9.0, end_time: 12.0 },
-            sunday: WorkingHours { start_time: 0.0, end_time: 0.0 },
+            monday: WorkingHours { start_time: at(9), end_time: at(17) },
+            tuesday: WorkingHours { start_time: at(9), end_time: at(17) },
+            wednesday: WorkingHours { start_time: at(9), end_time: at(17) },
+            thursday: WorkingHours { start_time: at(9), end_time: at(17) },
+            friday: WorkingHours { start_time: at(9), end_time: at(17) },
+            saturday: WorkingHours { start_time: at(9), end_time: at(12) },
+            sunday: WorkingHours { start_time: at(0), end_time: at(0) },
         };
 
-        contract.update_working_hours(working_hours.clone());
+        contract.update_working_hours(working_hours.clone(), None).unwrap();
 
         let stored_hours = contract.get_working_hours(accounts(1));
         assert_eq!(stored_hours, working_hours);
@@ -526,12 +1215,10 @@ mod tests {
 
     #[test]
     fn test_update_time_slots() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
         let time_slots = TimeSlots {
-            monday: vec![TimeSlot { start_time: 9.0, end_time: 10.0, task_id: Some(1) }],
+            monday: vec![TimeSlot { start_time: at(9), end_time: at(10), task_id: Some(1) }],
             tuesday: vec![],
             wednesday: vec![],
             thursday: vec![],
@@ -540,7 +1227,7 @@ mod tests {
             sunday: vec![],
         };
 
-        contract.update_time_slots(time_slots.clone());
+        contract.update_time_slots(time_slots.clone(), None).unwrap();
 
         let stored_slots = contract.get_time_slots(accounts(1)).unwrap();
         assert_eq!(stored_slots, time_slots);
@@ -548,46 +1235,40 @@ mod tests {
 
     #[test]
     fn test_add_regular_break() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_break(9.0, 10.0, true, None);
+        contract.add_break(at(9), at(10), true, None, None).unwrap();
 
         let breaks = contract.get_breaks(accounts(1));
         assert_eq!(breaks.regular_breaks.len(), 1);
         assert_eq!(breaks.one_time_breaks.len(), 0);
         let regular_break = &breaks.regular_breaks[0];
-        assert_eq!(regular_break.start_time, 9.0);
-        assert_eq!(regular_break.end_time, 10.0);
+        assert_eq!(regular_break.start_time, at(9));
+        assert_eq!(regular_break.end_time, at(10));
         assert!(regular_break.date.is_none());
     }
 
     #[test]
     fn test_add_one_time_break() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_break(9.0, 10.0, false, Some(1_640_995_200_000));
+        contract.add_break(at(9), at(10), false, Some(1_640_995_200_000), None).unwrap();
 
         let breaks = contract.get_breaks(accounts(1));
         assert_eq!(breaks.regular_breaks.len(), 0);
         assert_eq!(breaks.one_time_breaks.len(), 1);
         let one_time_break = &breaks.one_time_breaks[0];
-        assert_eq!(one_time_break.start_time, 9.0);
-        assert_eq!(one_time_break.end_time, 10.0);
+        assert_eq!(one_time_break.start_time, at(9));
+        assert_eq!(one_time_break.end_time, at(10));
         assert_eq!(one_time_break.date, Some(1_640_995_200_000));
     }
 
     #[test]
     fn test_remove_regular_break() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_break(9.0, 10.0, true, None);
-        contract.remove_break(9.0, 10.0, true, None);
+        contract.add_break(at(9), at(10), true, None, None).unwrap();
+        contract.remove_break(at(9), at(10), true, None, None).unwrap();
         
         let breaks = contract.get_breaks(accounts(1));
         assert_eq!(breaks.regular_breaks.len(), 0);
@@ -595,48 +1276,58 @@ mod tests {
 
     #[test]
     fn test_remove_one_time_break() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_break(9.0, 10.0, false, Some(1_640_995_200_000));
-        contract.remove_break(9.0, 10.0, false, Some(1_640_995_200_000));
+        contract.add_break(at(9), at(10), false, Some(1_640_995_200_000), None).unwrap();
+        contract.remove_break(at(9), at(10), false, Some(1_640_995_200_000), None).unwrap();
 
         let breaks = contract.get_breaks(accounts(1));
         assert_eq!(breaks.one_time_breaks.len(), 0);
     }
 
+    #[test]
+    fn test_break_errors() {
+        let mut contract = setup_contract(accounts(1));
+
+        assert_eq!(
+            contract.add_break(at(10), at(9), true, None, None),
+            Err(ContractError::InvalidTimeRange { start_time: at(10), end_time: at(9) })
+        );
+        assert_eq!(contract.remove_break(at(9), at(10), true, None, None), Err(ContractError::BreakNotFound));
+
+        contract.add_break(at(9), at(10), true, None, None).unwrap();
+        assert_eq!(contract.update_break(at(9), at(11), at(12), at(13), true, None, None), Err(ContractError::BreakNotFound));
+        assert_eq!(contract.remove_break(at(9), at(10), false, None, None), Err(ContractError::BreakNotFound));
+        assert_eq!(contract.get_breaks(accounts(1)).regular_breaks.len(), 1);
+    }
+
     #[test]
     fn test_update_regular_break() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_break(9.0, 10.0, true, None);
-        contract.update_break(9.0, 10.0, 10.0, 11.0, true, None);
+        contract.add_break(at(9), at(10), true, None, None).unwrap();
+        contract.update_break(at(9), at(10), at(10), at(11), true, None, None).unwrap();
         
         let breaks = contract.get_breaks(accounts(1));
         assert_eq!(breaks.regular_breaks.len(), 1);
         let break_ = &breaks.regular_breaks[0];
-        assert_eq!(break_.start_time, 10.0);
-        assert_eq!(break_.end_time, 11.0);
-        assert_eq!(break_.is_regular, true);
+        assert_eq!(break_.start_time, at(10));
+        assert_eq!(break_.end_time, at(11));
+        assert!(break_.is_regular);
     }
 
     #[test]
     fn test_update_one_time_break() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        let mut contract = TodoListV1::new();
+        let mut contract = setup_contract(accounts(1));
 
-        contract.add_break(9.0, 10.0, false, Some(1_640_995_200_000));
-        contract.update_break(9.0, 10.0, 10.0, 11.0, false, Some(1_641_995_200_000));
+        contract.add_break(at(9), at(10), false, Some(1_640_995_200_000), None).unwrap();
+        contract.update_break(at(9), at(10), at(10), at(11), false, Some(1_641_995_200_000), None).unwrap();
 
         let breaks = contract.get_breaks(accounts(1));
         assert_eq!(breaks.one_time_breaks.len(), 1);
         let one_time_break = &breaks.one_time_breaks[0];
-        assert_eq!(one_time_break.start_time, 10.0);
-        assert_eq!(one_time_break.end_time, 11.0);
+        assert_eq!(one_time_break.start_time, at(10));
+        assert_eq!(one_time_break.end_time, at(11));
         assert_eq!(one_time_break.date, Some(1_641_995_200_000));
     }
 
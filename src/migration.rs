@@ -0,0 +1,588 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, Promise};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    AccountBreaks, AccountIdWrapper, Break, ContractError, DurationMinutes, PointsChange, Reward, RewardLimits, Task, TimeOfDay,
+    TimeSlot, TimeSlots, TodoListV1, TodoListV1Ext, WeeklyWorkingHours, WorkingHours,
+};
+
+/// Storage key of the state layout version. It lives outside the contract
+/// struct so it can be read before knowing how to deserialize the state.
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+/// Layout written by this code. Bump it together with a new `VersionedTodoList`
+/// variant whenever `TodoListV1` changes shape.
+pub const CURRENT_STATE_VERSION: u8 = 1;
+/// Where `migrate` sets the first release's state aside until
+/// `migrate_accounts` has moved every account out of it.
+const LEGACY_STATE_KEY: &[u8] = b"LEGACY_STATE";
+/// Kept back from the prepaid gas to finish `upgrade` itself after scheduling
+/// the deploy and `migrate` call.
+const GAS_RESERVED_FOR_UPGRADE: Gas = Gas(10_000_000_000_000);
+
+/// Every state layout the contract has been deployed with. `migrate` reads
+/// whichever one is stored and converts it to the current layout.
+// Only held for the duration of `migrate`, so boxing variants buys nothing.
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedTodoList {
+    /// The first release, which predates the version marker.
+    Legacy(LegacyTodoListV1),
+    V1(TodoListV1),
+}
+
+impl VersionedTodoList {
+    fn read() -> Self {
+        fn read_state<T: BorshDeserialize>() -> T {
+            env::state_read().unwrap_or_else(|| env::panic_str("No contract state to migrate"))
+        }
+        match env::storage_read(STATE_VERSION_KEY).as_deref() {
+            None => Self::Legacy(read_state()),
+            Some([1]) => Self::V1(read_state()),
+            Some(version) => env::panic_str(&format!("Unknown state version {:?}", version)),
+        }
+    }
+
+    /// `owner_id` replaces the stored owner. The first release had none, so
+    /// migrating from it requires one.
+    fn into_current(self, owner_id: Option<AccountId>) -> TodoListV1 {
+        let mut contract = match self {
+            Self::Legacy(old_state) => {
+                let owner_id = owner_id.clone().unwrap_or_else(|| env::panic_str("owner_id is required to migrate the first release"));
+                TodoListV1::from_legacy(old_state, owner_id)
+            }
+            Self::V1(contract) => contract,
+        };
+        if let Some(owner_id) = owner_id {
+            contract.owner_id = owner_id;
+        }
+        contract
+    }
+}
+
+pub(crate) fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &[CURRENT_STATE_VERSION]);
+}
+
+/// State layout of the first release, where every collection was a `HashMap`
+/// deserialized in full on each call.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyTodoListV1 {
+    tasks: HashMap<AccountId, Vec<LegacyTask>>,
+    rewards: HashMap<AccountId, Vec<LegacyReward>>,
+    account_reward_points: HashMap<AccountId, i64>,
+    completed_tasks_per_day: HashMap<AccountId, HashMap<String, u32>>,
+    working_hours: HashMap<AccountId, LegacyWeeklyWorkingHours>,
+    time_slots: HashMap<AccountId, LegacyTimeSlots>,
+    breaks: HashMap<AccountId, LegacyAccountBreaks>,
+}
+
+impl LegacyTodoListV1 {
+    /// Every account with data left, in order.
+    fn account_ids(&self) -> BTreeSet<AccountId> {
+        self.tasks
+            .keys()
+            .chain(self.rewards.keys())
+            .chain(self.account_reward_points.keys())
+            .chain(self.completed_tasks_per_day.keys())
+            .chain(self.working_hours.keys())
+            .chain(self.time_slots.keys())
+            .chain(self.breaks.keys())
+            .cloned()
+            .collect()
+    }
+}
+
+/// The first release stored times of day and durations as fractional hours.
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacyTask {
+    id: u64,
+    title: String,
+    description: String,
+    priority: u8,
+    deadline: Option<u64>,
+    estimated_time: Option<f64>,
+    reward_points: i64,
+    completed: bool,
+    owner: AccountIdWrapper,
+}
+
+/// Rewards had no limits before the catalog fields were added.
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacyReward {
+    id: u64,
+    title: String,
+    description: String,
+    cost: i64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyWorkingHours {
+    start_time: f64,
+    end_time: f64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyWeeklyWorkingHours {
+    monday: LegacyWorkingHours,
+    tuesday: LegacyWorkingHours,
+    wednesday: LegacyWorkingHours,
+    thursday: LegacyWorkingHours,
+    friday: LegacyWorkingHours,
+    saturday: LegacyWorkingHours,
+    sunday: LegacyWorkingHours,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyTimeSlot {
+    start_time: f64,
+    end_time: f64,
+    task_id: Option<u64>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyTimeSlots {
+    monday: Vec<LegacyTimeSlot>,
+    tuesday: Vec<LegacyTimeSlot>,
+    wednesday: Vec<LegacyTimeSlot>,
+    thursday: Vec<LegacyTimeSlot>,
+    friday: Vec<LegacyTimeSlot>,
+    saturday: Vec<LegacyTimeSlot>,
+    sunday: Vec<LegacyTimeSlot>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyBreak {
+    start_time: f64,
+    end_time: f64,
+    is_regular: bool,
+    date: Option<u64>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyAccountBreaks {
+    regular_breaks: Vec<LegacyBreak>,
+    one_time_breaks: Vec<LegacyBreak>,
+}
+
+impl From<LegacyTask> for Task {
+    fn from(task: LegacyTask) -> Self {
+        Task {
+            id: task.id,
+            title: task.title,
+            description: task.description,
+            priority: task.priority,
+            deadline: task.deadline,
+            estimated_time: task.estimated_time.and_then(DurationMinutes::from_legacy_hours),
+            reward_points: task.reward_points,
+            completed: task.completed,
+            completed_at: None,
+            completed_day: None,
+            credit: None,
+            actual_time: None,
+            recurrence: None,
+            next_occurrence: None,
+            parent_id: None,
+            depends_on: vec![],
+            project_id: None,
+            tag_ids: vec![],
+            assignee: None,
+            supervisor: None,
+            submission: None,
+            owner: task.owner,
+        }
+    }
+}
+
+impl From<LegacyReward> for Reward {
+    fn from(reward: LegacyReward) -> Self {
+        Reward {
+            id: reward.id,
+            title: reward.title,
+            description: reward.description,
+            cost: reward.cost,
+            limits: RewardLimits::default(),
+            active: true,
+            redeemed: 0,
+            recent_redemptions: vec![],
+            approver: None,
+        }
+    }
+}
+
+impl From<LegacyWorkingHours> for WorkingHours {
+    fn from(hours: LegacyWorkingHours) -> Self {
+        WorkingHours {
+            start_time: TimeOfDay::from_legacy_hours(hours.start_time),
+            end_time: TimeOfDay::from_legacy_hours(hours.end_time),
+        }
+    }
+}
+
+impl From<LegacyWeeklyWorkingHours> for WeeklyWorkingHours {
+    fn from(week: LegacyWeeklyWorkingHours) -> Self {
+        WeeklyWorkingHours {
+            monday: week.monday.into(),
+            tuesday: week.tuesday.into(),
+            wednesday: week.wednesday.into(),
+            thursday: week.thursday.into(),
+            friday: week.friday.into(),
+            saturday: week.saturday.into(),
+            sunday: week.sunday.into(),
+        }
+    }
+}
+
+impl From<LegacyTimeSlot> for TimeSlot {
+    fn from(slot: LegacyTimeSlot) -> Self {
+        TimeSlot {
+            start_time: TimeOfDay::from_legacy_hours(slot.start_time),
+            end_time: TimeOfDay::from_legacy_hours(slot.end_time),
+            task_id: slot.task_id,
+        }
+    }
+}
+
+impl From<LegacyTimeSlots> for TimeSlots {
+    fn from(week: LegacyTimeSlots) -> Self {
+        let convert = |slots: Vec<LegacyTimeSlot>| slots.into_iter().map(TimeSlot::from).collect();
+        TimeSlots {
+            monday: convert(week.monday),
+            tuesday: convert(week.tuesday),
+            wednesday: convert(week.wednesday),
+            thursday: convert(week.thursday),
+            friday: convert(week.friday),
+            saturday: convert(week.saturday),
+            sunday: convert(week.sunday),
+        }
+    }
+}
+
+impl From<LegacyBreak> for Break {
+    fn from(legacy: LegacyBreak) -> Self {
+        Break {
+            start_time: TimeOfDay::from_legacy_hours(legacy.start_time),
+            end_time: TimeOfDay::from_legacy_hours(legacy.end_time),
+            is_regular: legacy.is_regular,
+            date: legacy.date,
+        }
+    }
+}
+
+impl From<LegacyAccountBreaks> for AccountBreaks {
+    fn from(breaks: LegacyAccountBreaks) -> Self {
+        AccountBreaks {
+            regular_breaks: breaks.regular_breaks.into_iter().map(Break::from).collect(),
+            one_time_breaks: breaks.one_time_breaks.into_iter().map(Break::from).collect(),
+        }
+    }
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Converts the stored state, whatever its version, to the current layout.
+    /// The first release's accounts are then moved by `migrate_accounts`.
+    /// Pass `owner_id` to hand the contract to a new owner; migrating the first
+    /// release requires it, since that state has no owner to carry over.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate(owner_id: Option<AccountId>) -> Self {
+        let contract = VersionedTodoList::read().into_current(owner_id);
+        write_state_version();
+        contract
+    }
+
+    /// Deploys the Wasm passed as the raw call input and runs `migrate` with it.
+    /// Only the owner can upgrade the contract.
+    #[handle_result]
+    pub fn upgrade(&self) -> Result<Promise, ContractError> {
+        if env::predecessor_account_id() != self.owner_id {
+            return Err(ContractError::Unauthorized);
+        }
+        let code = env::input().unwrap_or_else(|| env::panic_str("Expected the new contract code as input"));
+        let remaining_gas = env::prepaid_gas() - env::used_gas();
+        if remaining_gas < GAS_RESERVED_FOR_UPGRADE {
+            return Err(ContractError::InsufficientGas { required: GAS_RESERVED_FOR_UPGRADE.0 });
+        }
+        let gas = remaining_gas - GAS_RESERVED_FOR_UPGRADE;
+        Ok(Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), b"{}".to_vec(), 0, gas))
+    }
+
+    /// Moves up to `limit` accounts left over from the first release into the
+    /// current layout and returns how many remain. Until an account is moved
+    /// its data does not show. Only the owner can run it.
+    #[handle_result]
+    pub fn migrate_accounts(&mut self, limit: u32) -> Result<u32, ContractError> {
+        if env::predecessor_account_id() != self.owner_id {
+            return Err(ContractError::Unauthorized);
+        }
+        // Each batch reads the whole remaining legacy state once, which every
+        // call to the first release did as well.
+        let Some(mut old_state) = env::storage_read(LEGACY_STATE_KEY).and_then(|bytes| LegacyTodoListV1::try_from_slice(&bytes).ok()) else {
+            return Ok(0);
+        };
+        let account_ids = old_state.account_ids();
+        for account_id in account_ids.iter().take(limit as usize) {
+            self.migrate_legacy_account(&mut old_state, account_id.clone())?;
+        }
+
+        let remaining = account_ids.len().saturating_sub(limit as usize) as u32;
+        if remaining == 0 {
+            env::storage_remove(LEGACY_STATE_KEY);
+        } else {
+            env::storage_write(LEGACY_STATE_KEY, &old_state.try_to_vec().unwrap_or_else(|_| env::abort()));
+        }
+        Ok(remaining)
+    }
+
+    pub fn get_version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+}
+
+impl TodoListV1 {
+    /// Starts from empty collections and sets the `LegacyTodoListV1` state
+    /// aside for `migrate_accounts`, which moves it over in batches.
+    fn from_legacy(old_state: LegacyTodoListV1, owner_id: AccountId) -> Self {
+        env::storage_write(LEGACY_STATE_KEY, &old_state.try_to_vec().unwrap_or_else(|_| env::abort()));
+        Self { owner_id, ..Self::new() }
+    }
+
+    /// Moves one account's `LegacyTodoListV1` data into the per-account
+    /// persistent collections, rounding its fractional hours to whole minutes.
+    fn migrate_legacy_account(&mut self, old_state: &mut LegacyTodoListV1, account_id: AccountId) -> Result<(), ContractError> {
+        self.register_existing_account(account_id.clone());
+
+        // V1 derived IDs from the list length, so removals could leave duplicates
+        // behind. Later duplicates get fresh IDs past the account's highest one,
+        // or past any the account has created since the upgrade.
+        if let Some(tasks) = old_state.tasks.remove(&account_id) {
+            let legacy_next_id = tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1;
+            let mut next_id = self.next_task_ids.get(&account_id).map_or(legacy_next_id, |next_id| legacy_next_id.max(*next_id));
+            let account_tasks = self.account_tasks_mut(&account_id);
+            for task in tasks {
+                let mut task = Task::from(task);
+                if account_tasks.contains_key(&task.id) {
+                    task.id = next_id;
+                    next_id += 1;
+                }
+                account_tasks.insert(task.id, task);
+            }
+            self.next_task_ids.insert(account_id.clone(), next_id);
+        }
+
+        if let Some(rewards) = old_state.rewards.remove(&account_id) {
+            let legacy_next_id = rewards.iter().map(|reward| reward.id).max().unwrap_or(0) + 1;
+            let mut next_id = self.next_reward_ids.get(&account_id).map_or(legacy_next_id, |next_id| legacy_next_id.max(*next_id));
+            let account_rewards = self.account_rewards_mut(&account_id);
+            for reward in rewards {
+                let mut reward = Reward::from(reward);
+                if account_rewards.contains_key(&reward.id) {
+                    reward.id = next_id;
+                    next_id += 1;
+                }
+                account_rewards.insert(reward.id, reward);
+            }
+            self.next_reward_ids.insert(account_id.clone(), next_id);
+        }
+
+        if let Some(days) = old_state.completed_tasks_per_day.remove(&account_id) {
+            let account_days = self.account_completed_tasks_per_day_mut(&account_id);
+            let mut completed = 0u32;
+            // Days were stored as decimal strings.
+            for (day, count) in days {
+                if let Ok(day) = day.parse::<u32>() {
+                    *account_days.entry(day).or_insert(0) += count;
+                    completed = completed.saturating_add(count);
+                }
+            }
+            if completed > 0 {
+                let total = self.total_completed_tasks(&account_id).saturating_add(completed);
+                self.completed_tasks_total.insert(account_id.clone(), total);
+            }
+        }
+
+        if let Some(points) = old_state.account_reward_points.remove(&account_id) {
+            let reason = "Balance carried over from before the ledger".to_string();
+            self.change_points(&account_id, points, PointsChange::Adjustment { reason })?;
+        }
+        if let Some(hours) = old_state.working_hours.remove(&account_id) {
+            self.working_hours.insert(account_id.clone(), hours.into());
+        }
+        if let Some(slots) = old_state.time_slots.remove(&account_id) {
+            self.time_slots.insert(account_id.clone(), slots.into());
+        }
+        if let Some(breaks) = old_state.breaks.remove(&account_id) {
+            self.breaks.insert(account_id, breaks.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{at, get_context, setup_contract};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    #[test]
+    fn test_migrate_from_legacy_state() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let task = LegacyTask {
+            id: 1,
+            title: "Legacy Task".to_string(),
+            description: "Task Description".to_string(),
+            priority: 1,
+            deadline: None,
+            estimated_time: Some(1.5),
+            reward_points: 10,
+            completed: false,
+            owner: AccountIdWrapper(accounts(1)),
+        };
+        let reward = LegacyReward {
+            id: 1,
+            title: "Reward".to_string(),
+            description: "Reward Description".to_string(),
+            cost: 50,
+        };
+        let legacy = LegacyTodoListV1 {
+            tasks: HashMap::from([(accounts(1), vec![task.clone()])]),
+            rewards: HashMap::from([(accounts(1), vec![reward.clone()])]),
+            account_reward_points: HashMap::from([(accounts(1), 70)]),
+            completed_tasks_per_day: HashMap::from([(accounts(1), HashMap::from([("19000".to_string(), 2)]))]),
+            working_hours: HashMap::new(),
+            time_slots: HashMap::new(),
+            breaks: HashMap::from([(
+                accounts(1),
+                LegacyAccountBreaks {
+                    regular_breaks: vec![LegacyBreak { start_time: 12.0, end_time: 12.75, is_regular: true, date: None }],
+                    one_time_breaks: vec![],
+                },
+            )]),
+        };
+        env::state_write(&legacy);
+
+        let mut contract = TodoListV1::migrate(Some(accounts(1)));
+        assert!(contract.get_tasks(accounts(1)).is_empty());
+        testing_env!(get_context(accounts(1)).build());
+        assert_eq!(contract.migrate_accounts(10), Ok(0));
+        let tasks = contract.get_tasks(accounts(1));
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, task.title);
+        assert_eq!(tasks[0].estimated_time, Some(DurationMinutes::from_hm(1, 30)));
+        assert_eq!(
+            contract.get_breaks(accounts(1)).regular_breaks,
+            vec![Break { start_time: at(12), end_time: TimeOfDay::from_hm(12, 45), is_regular: true, date: None }]
+        );
+        assert_eq!(contract.get_rewards(accounts(1)), vec![Reward::from(reward)]);
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 70);
+        assert_eq!(contract.get_completed_tasks_per_day(accounts(1)).get(&19000), Some(&2));
+        assert_eq!(contract.total_completed_tasks(&accounts(1)), 2);
+        assert!(contract.get_tasks(accounts(2)).is_empty());
+        assert_eq!(contract.storage_balance_of(accounts(1)).map(|balance| balance.total.0), Some(0));
+    }
+
+    #[test]
+    fn test_migrate_repairs_duplicate_ids() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let task = |id: u64, title: &str| LegacyTask {
+            id,
+            title: title.to_string(),
+            description: "".to_string(),
+            priority: 1,
+            deadline: None,
+            estimated_time: None,
+            reward_points: 10,
+            completed: false,
+            owner: AccountIdWrapper(accounts(1)),
+        };
+        // Produced by V1 after: add, add, remove(1), add.
+        let legacy = LegacyTodoListV1 {
+            tasks: HashMap::from([(accounts(1), vec![task(2, "Second"), task(2, "Third")])]),
+            rewards: HashMap::new(),
+            account_reward_points: HashMap::new(),
+            completed_tasks_per_day: HashMap::new(),
+            working_hours: HashMap::new(),
+            time_slots: HashMap::new(),
+            breaks: HashMap::new(),
+        };
+        env::state_write(&legacy);
+
+        let mut contract = TodoListV1::migrate(Some(accounts(0)));
+        assert_eq!(contract.migrate_accounts(10), Ok(0));
+        let mut tasks = contract.get_tasks(accounts(1));
+        tasks.sort_by_key(|task| task.id);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!((tasks[0].id, tasks[0].title.as_str()), (2, "Second"));
+        assert_eq!((tasks[1].id, tasks[1].title.as_str()), (3, "Third"));
+
+        testing_env!(get_context(accounts(1)).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+        let new_id = contract.add_task("Fourth".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        assert_eq!(new_id, 4);
+    }
+
+    #[test]
+    fn test_migrate_accounts_in_batches() {
+        testing_env!(get_context(accounts(0)).build());
+        let legacy = LegacyTodoListV1 {
+            tasks: HashMap::new(),
+            rewards: HashMap::new(),
+            account_reward_points: HashMap::from([(accounts(1), 10), (accounts(2), 20), (accounts(3), 30)]),
+            completed_tasks_per_day: HashMap::new(),
+            working_hours: HashMap::new(),
+            time_slots: HashMap::new(),
+            breaks: HashMap::new(),
+        };
+        env::state_write(&legacy);
+
+        let mut contract = TodoListV1::migrate(Some(accounts(0)));
+        testing_env!(get_context(accounts(1)).build());
+        assert_eq!(contract.migrate_accounts(2), Err(ContractError::Unauthorized));
+        testing_env!(get_context(accounts(0)).build());
+        assert_eq!(contract.migrate_accounts(2), Ok(1));
+        assert_eq!(contract.get_account_reward_points(accounts(2)), 20);
+        assert_eq!(contract.get_account_reward_points(accounts(3)), 0);
+        assert_eq!(contract.migrate_accounts(2), Ok(0));
+        assert_eq!(contract.get_account_reward_points(accounts(3)), 30);
+        assert_eq!(env::storage_read(LEGACY_STATE_KEY), None);
+        assert_eq!(contract.migrate_accounts(2), Ok(0));
+    }
+
+    #[test]
+    fn test_migrate_current_state() {
+        let mut contract = setup_contract(accounts(1));
+        let task_id = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        env::state_write(&contract);
+
+        let contract = TodoListV1::migrate(None);
+        assert_eq!(contract.get_tasks(accounts(1)).len(), 1);
+        assert_eq!(contract.get_tasks(accounts(1))[0].id, task_id);
+        assert_eq!(env::storage_read(STATE_VERSION_KEY), Some(vec![CURRENT_STATE_VERSION]));
+        assert_eq!(contract.owner_id, accounts(1));
+    }
+
+    #[test]
+    fn test_upgrade_requires_owner() {
+        let contract = setup_contract(accounts(1));
+
+        let mut context = get_context(accounts(2)).build();
+        context.input = b"wasm".to_vec();
+        testing_env!(context.clone());
+        assert!(matches!(contract.upgrade(), Err(ContractError::Unauthorized)));
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        assert!(contract.upgrade().is_ok());
+
+        context.prepaid_gas = Gas(GAS_RESERVED_FOR_UPGRADE.0 / 2);
+        testing_env!(context);
+        assert!(matches!(contract.upgrade(), Err(ContractError::InsufficientGas { required }) if required == GAS_RESERVED_FOR_UPGRADE.0));
+    }
+}
@@ -0,0 +1,347 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::{self, json};
+use near_sdk::store::UnorderedSet;
+use near_sdk::{assert_one_yocto, env, ext_contract, near_bindgen, AccountId, Gas, Promise, PromiseOrValue, PromiseResult};
+use schemars::JsonSchema;
+
+use crate::events::{NftEvent, NftMint, NftTransfer};
+use crate::query::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::{account_hash, AccountIdWrapper, ContractError, Reward, StorageKey, TodoListV1, TodoListV1Ext};
+
+/// Version of the NEP-177 metadata standard `nft_metadata` follows.
+pub const NFT_METADATA_SPEC: &str = "nft-1.0.0";
+/// Completed task counts that earn an account a badge.
+pub const MILESTONES: [u32; 4] = [10, 100, 500, 1000];
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+/// Covers `nft_resolve_transfer` plus the cross-contract call overhead; the
+/// rest of the prepaid gas goes to `nft_on_transfer`.
+const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct NftContractMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub base_uri: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug, Default)]
+pub struct TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<String>,
+    pub copies: Option<u64>,
+    /// Milliseconds since the Unix epoch, as a string.
+    pub issued_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub starts_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// JSON describing what the badge was earned for.
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct Token {
+    token_id: String,
+    owner_id: AccountIdWrapper,
+    metadata: TokenMetadata,
+}
+
+#[ext_contract(ext_nft_receiver)]
+pub trait NonFungibleTokenReceiver {
+    fn nft_on_transfer(&mut self, sender_id: AccountId, previous_owner_id: AccountId, token_id: String, msg: String) -> PromiseOrValue<bool>;
+}
+
+fn badge_metadata(title: String, description: String, extra: serde_json::Value) -> TokenMetadata {
+    TokenMetadata {
+        title: Some(title),
+        description: Some(description),
+        copies: Some(1),
+        issued_at: Some((env::block_timestamp() / 1_000_000).to_string()),
+        extra: Some(extra.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Badges as NEP-171 tokens with NEP-177 metadata and NEP-181 enumeration.
+/// They are minted for redeemed rewards and completion milestones. Approvals
+/// (NEP-178) are not supported, so only the owner can transfer a badge, and
+/// the receiver must be registered through `storage_deposit`.
+#[near_bindgen]
+impl TodoListV1 {
+    #[payable]
+    #[handle_result]
+    pub fn nft_transfer(&mut self, receiver_id: AccountId, token_id: String, approval_id: Option<u64>, memo: Option<String>) -> Result<(), ContractError> {
+        assert_one_yocto();
+        if approval_id.is_some() {
+            return Err(ContractError::Unauthorized);
+        }
+        let sender_id = env::predecessor_account_id();
+        self.transfer_badge(&sender_id, &receiver_id, &token_id, memo.as_deref())
+    }
+
+    /// Transfers to a contract and calls its `nft_on_transfer`. The badge comes
+    /// back if that returns `true`, see `nft_resolve_transfer`.
+    #[payable]
+    #[handle_result]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> Result<Promise, ContractError> {
+        assert_one_yocto();
+        if approval_id.is_some() {
+            return Err(ContractError::Unauthorized);
+        }
+        let sender_id = env::predecessor_account_id();
+        self.transfer_badge(&sender_id, &receiver_id, &token_id, memo.as_deref())?;
+
+        let remaining_gas = env::prepaid_gas() - env::used_gas();
+        if remaining_gas < GAS_FOR_NFT_TRANSFER_CALL {
+            return Err(ContractError::InsufficientGas { required: GAS_FOR_NFT_TRANSFER_CALL.0 });
+        }
+        let receiver_gas = remaining_gas - GAS_FOR_NFT_TRANSFER_CALL;
+        Ok(ext_nft_receiver::ext(receiver_id.clone())
+            .with_static_gas(receiver_gas)
+            .nft_on_transfer(sender_id.clone(), sender_id.clone(), token_id.clone(), msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .nft_resolve_transfer(sender_id, receiver_id, token_id),
+            ))
+    }
+
+    /// Returns whether the badge stayed with the receiver. It goes back to the
+    /// previous owner when the receiver asked for that or failed, as long as the
+    /// receiver still holds it and the previous owner is still registered.
+    #[private]
+    pub fn nft_resolve_transfer(&mut self, owner_id: AccountId, receiver_id: AccountId, token_id: String) -> bool {
+        let return_token = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<bool>(&value).unwrap_or(true),
+            _ => true,
+        };
+        if !return_token
+            || !self.storage_accounts.contains_key(&owner_id)
+            || self.nft_tokens.get(&token_id).is_none_or(|token| token.owner_id.0 != receiver_id)
+        {
+            return true;
+        }
+        // The previous owner paid for the badge until just now, so a deposit
+        // short of covering it again does not stop the refund.
+        let _ = self.move_badge(&token_id, &receiver_id, &owner_id, None);
+        false
+    }
+
+    pub fn nft_token(&self, token_id: String) -> Option<Token> {
+        self.nft_tokens.get(&token_id).cloned()
+    }
+
+    pub fn nft_metadata(&self) -> NftContractMetadata {
+        NftContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "Todo Badges".to_string(),
+            symbol: "BADGE".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    pub fn nft_total_supply(&self) -> U128 {
+        U128(self.nft_tokens.len().into())
+    }
+
+    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        self.nft_tokens
+            .values()
+            .skip(from_index.map_or(0, |index| index.0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE) as usize)
+            .cloned()
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        U128(self.nft_owner_tokens.get(&account_id).map_or(0, |token_ids| token_ids.len()).into())
+    }
+
+    pub fn nft_tokens_for_owner(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        let Some(token_ids) = self.nft_owner_tokens.get(&account_id) else {
+            return vec![];
+        };
+        token_ids
+            .iter()
+            .skip(from_index.map_or(0, |index| index.0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE) as usize)
+            .filter_map(|token_id| self.nft_tokens.get(token_id))
+            .cloned()
+            .collect()
+    }
+}
+
+impl TodoListV1 {
+    /// Mints a badge recording the redemption of `reward` to `owner_id` and
+    /// returns its ID.
+    pub(crate) fn mint_reward_badge(&mut self, owner_id: &AccountId, reward: &Reward) -> String {
+        let extra = json!({ "reward_id": reward.id, "cost": reward.cost });
+        self.mint_badge(owner_id, badge_metadata(reward.title.clone(), reward.description.clone(), extra))
+    }
+
+    /// Who gets the badge for a redemption from `account_id`'s list by
+    /// `redeemer_id`: the member who redeemed for a workspace, which cannot
+    /// sign to transfer badges, otherwise the list's account.
+    pub(crate) fn redemption_badge_owner(&self, account_id: &AccountId, redeemer_id: AccountId) -> AccountId {
+        if self.workspaces.contains_key(account_id) {
+            redeemer_id
+        } else {
+            account_id.clone()
+        }
+    }
+
+    /// Mints a badge to `owner_id` for every milestone the account's completed
+    /// task count has passed since the last one. Reopening tasks does not take
+    /// them back.
+    pub(crate) fn award_milestones(&mut self, account_id: &AccountId, owner_id: &AccountId) {
+        let completed = self.total_completed_tasks(account_id);
+        let reached = self.milestones_reached.get(account_id).copied().unwrap_or(0);
+        for milestone in MILESTONES.into_iter().filter(|milestone| *milestone > reached && *milestone <= completed) {
+            let metadata = badge_metadata(
+                format!("{} tasks completed", milestone),
+                format!("Completed {} tasks", milestone),
+                json!({ "milestone": milestone }),
+            );
+            self.mint_badge(owner_id, metadata);
+            self.milestones_reached.insert(account_id.clone(), milestone);
+        }
+    }
+
+    /// Burns every badge the account holds.
+    pub(crate) fn burn_badges(&mut self, account_id: &AccountId) {
+        if let Some(mut token_ids) = self.nft_owner_tokens.remove(account_id) {
+            for token_id in token_ids.iter() {
+                self.nft_tokens.remove(token_id);
+                NftEvent::NftBurn([NftMint { owner_id: account_id, token_ids: vec![token_id], memo: None }]).emit();
+            }
+            token_ids.clear();
+        }
+        self.milestones_reached.remove(account_id);
+    }
+
+    fn mint_badge(&mut self, owner_id: &AccountId, metadata: TokenMetadata) -> String {
+        let token_id = self.next_nft_id.to_string();
+        self.next_nft_id += 1;
+        NftEvent::NftMint([NftMint { owner_id, token_ids: vec![&token_id], memo: None }]).emit();
+        let token = Token { token_id: token_id.clone(), owner_id: AccountIdWrapper(owner_id.clone()), metadata };
+        self.nft_tokens.insert(token_id.clone(), token);
+        let token_ids = self.owner_tokens_mut(owner_id);
+        token_ids.insert(token_id.clone());
+        // Written out now so the call's storage counts it, whoever the owner is.
+        token_ids.flush();
+        token_id
+    }
+
+    fn transfer_badge(&mut self, sender_id: &AccountId, receiver_id: &AccountId, token_id: &str, memo: Option<&str>) -> Result<(), ContractError> {
+        let token = self.nft_tokens.get(token_id).ok_or_else(|| ContractError::NftNotFound(token_id.to_string()))?;
+        if token.owner_id.0 != *sender_id {
+            return Err(ContractError::Unauthorized);
+        }
+        if sender_id == receiver_id {
+            return Err(ContractError::SelfTransfer);
+        }
+        if !self.storage_accounts.contains_key(receiver_id) {
+            return Err(ContractError::NotRegistered(receiver_id.clone()));
+        }
+        self.move_badge(token_id, sender_id, receiver_id, memo)
+    }
+
+    /// Hands the badge from one account to the other, each paying for their
+    /// own side of the change.
+    fn move_badge(&mut self, token_id: &str, old_owner_id: &AccountId, new_owner_id: &AccountId, memo: Option<&str>) -> Result<(), ContractError> {
+        let initial_storage_usage = self.begin_storage_tracking(old_owner_id)?;
+        let now_empty = self.nft_owner_tokens.get_mut(old_owner_id).is_some_and(|token_ids| {
+            token_ids.remove(token_id);
+            token_ids.is_empty()
+        });
+        if now_empty {
+            self.nft_owner_tokens.remove(old_owner_id);
+        }
+        self.settle_storage(old_owner_id, initial_storage_usage)?;
+
+        let initial_storage_usage = self.begin_storage_tracking(new_owner_id)?;
+        if let Some(token) = self.nft_tokens.get_mut(token_id) {
+            token.owner_id = AccountIdWrapper(new_owner_id.clone());
+        }
+        self.owner_tokens_mut(new_owner_id).insert(token_id.to_string());
+        NftEvent::NftTransfer([NftTransfer { old_owner_id, new_owner_id, token_ids: vec![token_id], memo }]).emit();
+        self.settle_storage(new_owner_id, initial_storage_usage)
+    }
+
+    fn owner_tokens_mut(&mut self, account_id: &AccountId) -> &mut UnorderedSet<String> {
+        self.nft_owner_tokens.entry(account_id.clone()).or_insert_with(|| {
+            UnorderedSet::new(StorageKey::AccountNftTokens { account_hash: account_hash(account_id) })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    #[test]
+    fn test_redeeming_mints_badge() {
+        let mut contract = setup_contract(accounts(1));
+        let task = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 50, None).unwrap();
+        let reward = contract.add_reward("Cinema".to_string(), "A film of choice".to_string(), 50, None).unwrap();
+        contract.mark_complete(task, None).unwrap();
+
+        let token_id = contract.redeem_reward(reward, None).unwrap();
+        let token = contract.nft_token(token_id.clone()).unwrap();
+        assert_eq!(token.owner_id.0, accounts(1));
+        assert_eq!(token.metadata.title.as_deref(), Some("Cinema"));
+        assert_eq!(token.metadata.extra.as_deref(), Some(r#"{"cost":50,"reward_id":1}"#));
+        assert_eq!(contract.nft_supply_for_owner(accounts(1)), U128(1));
+
+        testing_env!(get_context(accounts(2)).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+        testing_env!(get_context(accounts(2)).attached_deposit(1).build());
+        assert_eq!(contract.nft_transfer(accounts(2), token_id.clone(), None, None), Err(ContractError::Unauthorized));
+
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        contract.nft_transfer(accounts(2), token_id.clone(), None, None).unwrap();
+        assert!(contract.nft_tokens_for_owner(accounts(1), None, None).is_empty());
+        assert_eq!(contract.nft_tokens_for_owner(accounts(2), None, None), vec![contract.nft_token(token_id).unwrap()]);
+        assert_eq!(contract.nft_transfer(accounts(2), "9".to_string(), None, None), Err(ContractError::NftNotFound("9".to_string())));
+    }
+
+    #[test]
+    fn test_milestone_badges() {
+        let mut contract = setup_contract(accounts(1));
+        for i in 0..11 {
+            let task = contract.add_task(format!("Task {}", i), "".to_string(), 1, None, None, 1, None).unwrap();
+            contract.mark_complete(task, None).unwrap();
+            if i == 9 {
+                contract.mark_incomplete(task, None).unwrap();
+                contract.mark_complete(task, None).unwrap();
+            }
+        }
+
+        let badges = contract.nft_tokens_for_owner(accounts(1), None, None);
+        assert_eq!(badges.len(), 1);
+        assert_eq!(badges[0].metadata.title.as_deref(), Some("10 tasks completed"));
+        assert_eq!(contract.nft_total_supply(), U128(1));
+    }
+}
@@ -0,0 +1,188 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{Reward, Task, TodoListV1, TodoListV1Ext};
+
+/// Page size used when a paged view is called without `limit`.
+pub const DEFAULT_PAGE_SIZE: u64 = 50;
+/// Larger `limit`s are capped so a page stays within view gas.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSortKey {
+    #[default]
+    Id,
+    /// Tasks without a deadline come after all tasks that have one.
+    Deadline,
+    Priority,
+}
+
+/// Narrows and orders `get_tasks_paged` results. Every criterion is optional
+/// and they combine with AND.
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct TaskFilter {
+    pub completed: Option<bool>,
+    pub min_priority: Option<u8>,
+    pub max_priority: Option<u8>,
+    /// Only tasks with a deadline strictly before this timestamp.
+    pub deadline_before: Option<u64>,
+    /// Only tasks with a deadline strictly after this timestamp.
+    pub deadline_after: Option<u64>,
+    /// Case-insensitive substring of the title.
+    pub title_contains: Option<String>,
+    pub project_id: Option<u64>,
+    /// Only tasks carrying all of these tags.
+    pub tag_ids: Vec<u64>,
+    pub sort_by: TaskSortKey,
+    pub descending: bool,
+}
+
+impl TaskFilter {
+    fn matches(&self, task: &Task, title_query: Option<&str>) -> bool {
+        self.completed.is_none_or(|completed| task.completed == completed)
+            && self.min_priority.is_none_or(|min| task.priority >= min)
+            && self.max_priority.is_none_or(|max| task.priority <= max)
+            && self.deadline_before.is_none_or(|before| task.deadline.is_some_and(|deadline| deadline < before))
+            && self.deadline_after.is_none_or(|after| task.deadline.is_some_and(|deadline| deadline > after))
+            && title_query.is_none_or(|query| task.title.to_lowercase().contains(query))
+            && self.project_id.is_none_or(|project_id| task.project_id == Some(project_id))
+            && self.tag_ids.iter().all(|tag_id| task.tag_ids.contains(tag_id))
+    }
+
+    fn sort(&self, tasks: &mut [&Task]) {
+        match self.sort_by {
+            TaskSortKey::Id => tasks.sort_by_key(|task| task.id),
+            TaskSortKey::Deadline => tasks.sort_by_key(|task| (task.deadline.is_none(), task.deadline, task.id)),
+            TaskSortKey::Priority => tasks.sort_by_key(|task| (task.priority, task.id)),
+        }
+        if self.descending {
+            tasks.reverse();
+            // Tasks without a deadline stay last in either direction.
+            if matches!(self.sort_by, TaskSortKey::Deadline) {
+                let without_deadline = tasks.iter().take_while(|task| task.deadline.is_none()).count();
+                tasks.rotate_left(without_deadline);
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Returns one page of the account's tasks matching `filter`, sorted by ID
+    /// unless the filter says otherwise.
+    pub fn get_tasks_paged(&self, account_id: AccountId, from_index: Option<u64>, limit: Option<u64>, filter: Option<TaskFilter>) -> Vec<Task> {
+        let Some(tasks) = self.tasks.get(&account_id) else {
+            return vec![];
+        };
+        let filter = filter.unwrap_or_default();
+        let title_query = filter.title_contains.as_ref().map(|query| query.to_lowercase());
+
+        let mut matching: Vec<&Task> = tasks.values().filter(|task| filter.matches(task, title_query.as_deref())).collect();
+        filter.sort(&mut matching);
+        matching
+            .into_iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE) as usize)
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_task(&self, account_id: AccountId, id: u64) -> Option<Task> {
+        self.tasks.get(&account_id).and_then(|tasks| tasks.get(&id)).cloned()
+    }
+
+    /// Returns the tasks that exist among `ids`, in the order requested. At most
+    /// `MAX_PAGE_SIZE` IDs are looked up.
+    pub fn get_tasks_by_ids(&self, account_id: AccountId, ids: Vec<u64>) -> Vec<Task> {
+        let Some(tasks) = self.tasks.get(&account_id) else {
+            return vec![];
+        };
+        ids.iter().take(MAX_PAGE_SIZE as usize).filter_map(|id| tasks.get(id)).cloned().collect()
+    }
+
+    pub fn get_reward(&self, account_id: AccountId, id: u64) -> Option<Reward> {
+        self.rewards.get(&account_id).and_then(|rewards| rewards.get(&id)).cloned()
+    }
+
+    /// Returns the rewards that exist among `ids`, in the order requested. At
+    /// most `MAX_PAGE_SIZE` IDs are looked up.
+    pub fn get_rewards_by_ids(&self, account_id: AccountId, ids: Vec<u64>) -> Vec<Reward> {
+        let Some(rewards) = self.rewards.get(&account_id) else {
+            return vec![];
+        };
+        ids.iter().take(MAX_PAGE_SIZE as usize).filter_map(|id| rewards.get(id)).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_contract;
+    use near_sdk::test_utils::accounts;
+
+    fn ids(tasks: Vec<Task>) -> Vec<u64> {
+        tasks.into_iter().map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn test_get_tasks_paged() {
+        let mut contract = setup_contract(accounts(1));
+        for i in 0..5 {
+            contract.add_task(format!("Task {}", i), "".to_string(), 1, None, None, 10, None).unwrap();
+        }
+
+        assert_eq!(ids(contract.get_tasks_paged(accounts(1), None, Some(2), None)), vec![1, 2]);
+        assert_eq!(ids(contract.get_tasks_paged(accounts(1), Some(2), Some(2), None)), vec![3, 4]);
+        assert_eq!(ids(contract.get_tasks_paged(accounts(1), Some(4), Some(2), None)), vec![5]);
+        assert!(contract.get_tasks_paged(accounts(1), Some(5), None, None).is_empty());
+        assert!(contract.get_tasks_paged(accounts(2), None, None, None).is_empty());
+    }
+
+    #[test]
+    fn test_get_tasks_paged_filter_and_sort() {
+        let mut contract = setup_contract(accounts(1));
+        let report = contract.add_task("Write report".to_string(), "".to_string(), 2, Some(300), None, 10, None).unwrap();
+        let groceries = contract.add_task("Groceries".to_string(), "".to_string(), 4, None, None, 10, None).unwrap();
+        let review = contract.add_task("Review REPORT".to_string(), "".to_string(), 5, Some(100), None, 10, None).unwrap();
+        let taxes = contract.add_task("Taxes".to_string(), "".to_string(), 3, Some(200), None, 10, None).unwrap();
+        contract.mark_complete(taxes, None).unwrap();
+
+        let query = |filter: TaskFilter| ids(contract.get_tasks_paged(accounts(1), None, None, Some(filter)));
+        assert_eq!(
+            query(TaskFilter { sort_by: TaskSortKey::Deadline, ..Default::default() }),
+            vec![review, taxes, report, groceries]
+        );
+        assert_eq!(
+            query(TaskFilter { sort_by: TaskSortKey::Deadline, descending: true, ..Default::default() }),
+            vec![report, taxes, review, groceries]
+        );
+        assert_eq!(
+            query(TaskFilter { sort_by: TaskSortKey::Priority, descending: true, ..Default::default() }),
+            vec![review, groceries, taxes, report]
+        );
+        assert_eq!(query(TaskFilter { completed: Some(false), min_priority: Some(3), ..Default::default() }), vec![groceries, review]);
+        assert_eq!(query(TaskFilter { deadline_before: Some(300), deadline_after: Some(100), ..Default::default() }), vec![taxes]);
+        assert_eq!(query(TaskFilter { title_contains: Some("report".to_string()), ..Default::default() }), vec![report, review]);
+    }
+
+    #[test]
+    fn test_get_by_ids() {
+        let mut contract = setup_contract(accounts(1));
+        let first = contract.add_task("First".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        let second = contract.add_task("Second".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        let reward = contract.add_reward("Reward".to_string(), "".to_string(), 50, None).unwrap();
+
+        assert_eq!(contract.get_task(accounts(1), second).map(|task| task.title), Some("Second".to_string()));
+        assert_eq!(contract.get_task(accounts(1), 9), None);
+        assert_eq!(contract.get_task(accounts(2), first), None);
+        assert_eq!(ids(contract.get_tasks_by_ids(accounts(1), vec![second, 9, first])), vec![second, first]);
+
+        assert_eq!(contract.get_reward(accounts(1), reward).map(|reward| reward.cost), Some(50));
+        assert_eq!(contract.get_reward(accounts(1), 9), None);
+        assert_eq!(contract.get_rewards_by_ids(accounts(1), vec![9, reward]).len(), 1);
+        assert!(contract.get_rewards_by_ids(accounts(2), vec![reward]).is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::time::{civil_from_days, days_from_civil, days_in_month, weekday_index, NANOS_PER_DAY};
+use crate::{ContractError, Permission, Task, TodoEvent, TodoListV1, TodoListV1Ext};
+
+/// Longest interval of an `EveryNDays` rule, about ten years.
+const MAX_INTERVAL_DAYS: u32 = 3660;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(tag = "frequency", rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    Weekly { weekdays: Vec<Weekday> },
+    /// Months shorter than `day` use their last day instead.
+    Monthly { day: u8 },
+    EveryNDays { days: u32 },
+}
+
+/// How a task repeats. Occurrences fall on the dates the rule produces after the
+/// current one, keeping its time of day, and are dated by the task deadline.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct Recurrence {
+    #[serde(flatten)]
+    frequency: Frequency,
+    /// No occurrence is created past this timestamp.
+    until: Option<u64>,
+    /// Occurrences left, counting the task carrying the rule.
+    count: Option<u32>,
+}
+
+impl Recurrence {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        match &self.frequency {
+            Frequency::Weekly { weekdays } if weekdays.is_empty() => {
+                Err(ContractError::InvalidRecurrence("weekly recurrence needs at least one weekday"))
+            }
+            Frequency::Monthly { day } if !(1..=31).contains(day) => {
+                Err(ContractError::InvalidRecurrence("monthly recurrence day must be between 1 and 31"))
+            }
+            Frequency::EveryNDays { days: 0 } => Err(ContractError::InvalidRecurrence("interval must be at least one day")),
+            Frequency::EveryNDays { days } if *days > MAX_INTERVAL_DAYS => {
+                Err(ContractError::InvalidRecurrence("interval must be at most 3660 days"))
+            }
+            _ if self.count == Some(0) => Err(ContractError::InvalidRecurrence("count must be positive")),
+            _ => Ok(()),
+        }
+    }
+
+    /// The first date after `timestamp` the rule produces, at the same time of
+    /// day, or `None` if it is past the largest timestamp.
+    fn next_after(&self, timestamp: u64) -> Option<u64> {
+        let day = timestamp / NANOS_PER_DAY;
+        let time_of_day = timestamp % NANOS_PER_DAY;
+        let next_day = match &self.frequency {
+            Frequency::Daily => day + 1,
+            Frequency::EveryNDays { days } => day + u64::from(*days),
+            Frequency::Weekly { weekdays } => (day + 1..=day + 7)
+                .find(|candidate| weekdays.iter().any(|weekday| *weekday as u64 == weekday_index(*candidate)))
+                .unwrap_or(day + 7),
+            Frequency::Monthly { day: target } => {
+                let day_in = |year, month| u32::from(*target).min(days_in_month(year, month));
+                let (year, month, day_of_month) = civil_from_days(day);
+                let (year, month) = if day_of_month < day_in(year, month) {
+                    (year, month)
+                } else if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                days_from_civil(year, month, day_in(year, month))
+            }
+        };
+        next_day.checked_mul(NANOS_PER_DAY)?.checked_add(time_of_day)
+    }
+
+    /// The rule for the occurrence after the one dated `timestamp`, with the
+    /// date it falls on, or `None` once the series is over.
+    pub(crate) fn advance(&self, timestamp: u64) -> Result<Option<(u64, Recurrence)>, ContractError> {
+        let next = self.next_after(timestamp).ok_or(ContractError::InvalidRecurrence("next occurrence is out of range"))?;
+        if self.until.is_some_and(|until| next > until) || self.count == Some(1) {
+            return Ok(None);
+        }
+        let recurrence = Recurrence { count: self.count.map(|count| count - 1), ..self.clone() };
+        Ok(Some((next, recurrence)))
+    }
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Makes a task repeat, or stop repeating when `recurrence` is `None`.
+    /// Completing it then creates the next occurrence as a new task.
+    #[handle_result]
+    pub fn set_task_recurrence(&mut self, id: u64, recurrence: Option<Recurrence>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        if let Some(recurrence) = &recurrence {
+            recurrence.validate()?;
+        }
+
+        let task = self.task_mut(&account_id, id)?;
+        task.recurrence = recurrence;
+        TodoEvent::TaskUpdated { account_id: &account_id, task }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+}
+
+impl TodoListV1 {
+    /// Adds the occurrence following a just completed recurring task and returns
+    /// its ID. Occurrences are dated from the task deadline, or from the
+    /// completion time when it has none. A task completed again after being
+    /// reopened keeps the occurrence it already spawned, if that still exists.
+    pub(crate) fn spawn_next_occurrence(&mut self, account_id: &AccountId, task: &Task) -> Result<Option<u64>, ContractError> {
+        if let Some(id) = task.next_occurrence.filter(|id| self.tasks.get(account_id).is_some_and(|tasks| tasks.contains_key(id))) {
+            return Ok(Some(id));
+        }
+        let Some(recurrence) = &task.recurrence else {
+            return Ok(None);
+        };
+        let Some(anchor) = task.deadline.or(task.completed_at) else {
+            return Ok(None);
+        };
+        let Some((deadline, recurrence)) = recurrence.advance(anchor)? else {
+            return Ok(None);
+        };
+        let task_count = self.tasks.get(account_id).map_or(0, |tasks| tasks.len());
+        self.limits.validate_task_count(task_count)?;
+
+        let id = Self::take_next_id(&mut self.next_task_ids, account_id);
+        let next = Task {
+            id,
+            deadline: Some(deadline),
+            completed: false,
+            completed_at: None,
+            completed_day: None,
+            credit: None,
+            actual_time: None,
+            recurrence: Some(recurrence),
+            next_occurrence: None,
+            submission: None,
+            ..task.clone()
+        };
+        if let Some(supervisor) = &next.supervisor {
+            self.index_supervised_task(&supervisor.0, account_id, id);
+        }
+        TodoEvent::TaskCreated { account_id, task: &next }.emit();
+        let tasks = self.account_tasks_mut(account_id);
+        tasks.insert(id, next);
+        if let Some(completed) = tasks.get_mut(&task.id) {
+            completed.next_occurrence = Some(id);
+        }
+        Ok(Some(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_contract;
+    use near_sdk::test_utils::accounts;
+
+    /// Monday, 2022-01-03 17:00 UTC.
+    const MONDAY_EVENING: u64 = 1_641_229_200_000_000_000;
+    const HOUR: u64 = 3_600_000_000_000;
+
+    fn rule(frequency: Frequency) -> Recurrence {
+        Recurrence { frequency, until: None, count: None }
+    }
+
+    #[test]
+    fn test_next_after() {
+        let weekly = rule(Frequency::Weekly { weekdays: vec![Weekday::Wednesday, Weekday::Monday] });
+        assert_eq!(weekly.next_after(MONDAY_EVENING), Some(MONDAY_EVENING + 2 * NANOS_PER_DAY));
+        assert_eq!(weekly.next_after(MONDAY_EVENING + 2 * NANOS_PER_DAY), Some(MONDAY_EVENING + 7 * NANOS_PER_DAY));
+        assert_eq!(rule(Frequency::Daily).next_after(MONDAY_EVENING), Some(MONDAY_EVENING + NANOS_PER_DAY));
+        assert_eq!(rule(Frequency::EveryNDays { days: 3 }).next_after(MONDAY_EVENING), Some(MONDAY_EVENING + 3 * NANOS_PER_DAY));
+
+        let end_of_month = rule(Frequency::Monthly { day: 31 });
+        let jan_31 = end_of_month.next_after(MONDAY_EVENING).unwrap();
+        assert_eq!(jan_31, MONDAY_EVENING + 28 * NANOS_PER_DAY);
+        assert_eq!(end_of_month.next_after(jan_31), Some(jan_31 + 28 * NANOS_PER_DAY));
+        assert_eq!(rule(Frequency::Monthly { day: 3 }).next_after(MONDAY_EVENING), Some(MONDAY_EVENING + 31 * NANOS_PER_DAY));
+    }
+
+    #[test]
+    fn test_completing_recurring_task_spawns_next() {
+        let mut contract = setup_contract(accounts(1));
+        let first = contract.add_task("Chores".to_string(), "".to_string(), 1, Some(MONDAY_EVENING), None, 10, None).unwrap();
+        let recurrence = Recurrence { frequency: Frequency::Daily, until: None, count: Some(2) };
+        contract.set_task_recurrence(first, Some(recurrence), None).unwrap();
+
+        let second = contract.mark_complete(first, None).unwrap().unwrap();
+        let next = contract.get_task(accounts(1), second).unwrap();
+        assert_eq!((next.title.as_str(), next.deadline, next.completed), ("Chores", Some(MONDAY_EVENING + NANOS_PER_DAY), false));
+        assert_eq!(next.recurrence.as_ref().and_then(|recurrence| recurrence.count), Some(1));
+
+        assert_eq!(contract.mark_complete(second, None), Ok(None));
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 20);
+        assert_eq!(contract.get_tasks(accounts(1)).iter().filter(|task| task.completed).count(), 2);
+    }
+
+    #[test]
+    fn test_reopen_and_complete_reuses_occurrence() {
+        let mut contract = setup_contract(accounts(1));
+        let first = contract.add_task("Chores".to_string(), "".to_string(), 1, Some(MONDAY_EVENING), None, 10, None).unwrap();
+        contract.set_task_recurrence(first, Some(rule(Frequency::Daily)), None).unwrap();
+
+        let second = contract.mark_complete(first, None).unwrap().unwrap();
+        contract.mark_incomplete(first, None).unwrap();
+        assert_eq!(contract.mark_complete(first, None), Ok(Some(second)));
+        assert_eq!(contract.get_tasks(accounts(1)).len(), 2);
+
+        // Once the occurrence is gone, completing again spawns a new one.
+        contract.mark_incomplete(first, None).unwrap();
+        contract.remove_task(second, None).unwrap();
+        let third = contract.mark_complete(first, None).unwrap().unwrap();
+        assert!(third > second);
+        assert_eq!(contract.get_tasks(accounts(1)).len(), 2);
+    }
+
+    #[test]
+    fn test_occurrence_out_of_range() {
+        let mut contract = setup_contract(accounts(1));
+        let task = contract.add_task("Chores".to_string(), "".to_string(), 1, Some(u64::MAX - HOUR), None, 10, None).unwrap();
+        assert_eq!(
+            contract.set_task_recurrence(task, Some(rule(Frequency::EveryNDays { days: 1_000_000 })), None),
+            Err(ContractError::InvalidRecurrence("interval must be at most 3660 days"))
+        );
+        contract.set_task_recurrence(task, Some(rule(Frequency::Daily)), None).unwrap();
+
+        assert_eq!(contract.mark_complete(task, None), Err(ContractError::InvalidRecurrence("next occurrence is out of range")));
+    }
+
+    #[test]
+    fn test_recurrence_until() {
+        let mut contract = setup_contract(accounts(1));
+        let task = contract.add_task("Chores".to_string(), "".to_string(), 1, Some(MONDAY_EVENING), None, 10, None).unwrap();
+        let recurrence = Recurrence { frequency: Frequency::Daily, until: Some(MONDAY_EVENING + 12 * HOUR), count: None };
+        contract.set_task_recurrence(task, Some(recurrence), None).unwrap();
+
+        assert_eq!(contract.mark_complete(task, None), Ok(None));
+        assert_eq!(
+            contract.set_task_recurrence(task, Some(rule(Frequency::Weekly { weekdays: vec![] })), None),
+            Err(ContractError::InvalidRecurrence("weekly recurrence needs at least one weekday"))
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+use std::cmp::Reverse;
+
+use crate::time::{local_day, local_day_start, weekday_index};
+use crate::{AccountBreaks, ContractError, Permission, Task, TimeOfDay, TimeSlot, TimeSlots, TodoEvent, TodoListV1, TodoListV1Ext, WeeklyWorkingHours};
+
+const NANOS_PER_MINUTE: u64 = 60_000_000_000;
+/// One-time break dates are stored by clients as millisecond timestamps.
+const NANOS_PER_MILLI: u64 = 1_000_000;
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct ScheduleResult {
+    time_slots: TimeSlots,
+    unscheduled_task_ids: Vec<u64>,
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Places the account's incomplete tasks into the week starting at
+    /// `week_start` (a nanosecond timestamp of Monday midnight in the account's
+    /// UTC offset) and stores the result as its time slots.
+    #[handle_result]
+    pub fn generate_schedule(&mut self, week_start: u64, account_id: Option<AccountId>) -> Result<ScheduleResult, ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageSchedule)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+
+        let working_hours = self.get_working_hours(account_id.clone());
+        let breaks = self.get_breaks(account_id.clone());
+        let tasks = self.get_tasks(account_id.clone());
+        let utc_offset = self.get_utc_offset(account_id.clone());
+        let result = build_schedule(&tasks, &working_hours, &breaks, week_start, utc_offset)?;
+
+        TodoEvent::TimeSlotsUpdated { account_id: &account_id, time_slots: &result.time_slots }.emit();
+        self.time_slots.insert(account_id.clone(), result.time_slots.clone());
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(result)
+    }
+}
+
+/// Removes `cut` from every interval in `free`, keeping the pieces on either side.
+fn subtract_interval(free: Vec<(TimeOfDay, TimeOfDay)>, cut: (TimeOfDay, TimeOfDay)) -> Vec<(TimeOfDay, TimeOfDay)> {
+    let mut remaining = Vec::with_capacity(free.len() + 1);
+    for (start, end) in free {
+        if cut.1 <= start || cut.0 >= end {
+            remaining.push((start, end));
+            continue;
+        }
+        if cut.0 > start {
+            remaining.push((start, cut.0));
+        }
+        if cut.1 < end {
+            remaining.push((cut.1, end));
+        }
+    }
+    remaining
+}
+
+/// Deterministically fits incomplete tasks into free working time. Tasks are
+/// taken by earliest deadline, then highest priority, then ID, and each one is
+/// placed whole into the earliest gap that ends before its deadline. Tasks
+/// without an estimate or without a large enough gap are reported back. Days
+/// are local to `utc_offset`.
+pub(crate) fn build_schedule(
+    tasks: &[Task],
+    working_hours: &WeeklyWorkingHours,
+    breaks: &AccountBreaks,
+    week_start: u64,
+    utc_offset: i16,
+) -> Result<ScheduleResult, ContractError> {
+    let first_day = local_day(week_start, utc_offset);
+    if local_day_start(first_day, utc_offset) != week_start || weekday_index(u64::from(first_day)) != 0 {
+        return Err(ContractError::InvalidWeekStart(week_start));
+    }
+
+    let mut free_time: Vec<Vec<(TimeOfDay, TimeOfDay)>> = working_hours
+        .days()
+        .iter()
+        .enumerate()
+        .map(|(offset, hours)| {
+            let day = first_day + offset as u32;
+            let mut free = if hours.start_time < hours.end_time {
+                vec![(hours.start_time, hours.end_time)]
+            } else {
+                vec![]
+            };
+            let one_time_breaks = breaks
+                .one_time_breaks
+                .iter()
+                .filter(|b| b.date.is_some_and(|date| local_day(date.saturating_mul(NANOS_PER_MILLI), utc_offset) == day));
+            for b in breaks.regular_breaks.iter().chain(one_time_breaks) {
+                free = subtract_interval(free, (b.start_time, b.end_time));
+            }
+            free
+        })
+        .collect();
+
+    let mut pending: Vec<&Task> = tasks.iter().filter(|task| !task.completed).collect();
+    pending.sort_by_key(|task| (task.deadline.is_none(), task.deadline, Reverse(task.priority), task.id));
+
+    let mut slots: [Vec<TimeSlot>; 7] = Default::default();
+    let mut unscheduled_task_ids = vec![];
+    for task in pending {
+        let Some(duration) = task.estimated_time else {
+            unscheduled_task_ids.push(task.id);
+            continue;
+        };
+
+        let placement = free_time.iter().enumerate().find_map(|(offset, free)| {
+            let day_start = local_day_start(first_day + offset as u32, utc_offset);
+            free.iter().find_map(|&(start, end)| {
+                let slot_end = u32::from(start.minutes()) + duration.minutes();
+                if slot_end > u32::from(end.minutes()) {
+                    return None;
+                }
+                let slot_end = TimeOfDay::from_minutes(slot_end as u16)?;
+                let fits_deadline = task
+                    .deadline
+                    .is_none_or(|deadline| day_start + u64::from(slot_end.minutes()) * NANOS_PER_MINUTE <= deadline);
+                fits_deadline.then_some((offset, start, slot_end))
+            })
+        });
+
+        match placement {
+            Some((offset, start_time, end_time)) => {
+                free_time[offset] = subtract_interval(std::mem::take(&mut free_time[offset]), (start_time, end_time));
+                slots[offset].push(TimeSlot { start_time, end_time, task_id: Some(task.id) });
+            }
+            None => unscheduled_task_ids.push(task.id),
+        }
+    }
+
+    for day in slots.iter_mut() {
+        day.sort_by_key(|slot| slot.start_time);
+    }
+    Ok(ScheduleResult {
+        time_slots: TimeSlots::from_days(slots),
+        unscheduled_task_ids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{at, setup_contract};
+    use crate::time::NANOS_PER_DAY;
+    use crate::DurationMinutes;
+    use near_sdk::test_utils::accounts;
+
+    /// Monday, 2022-01-03 00:00 UTC.
+    const MONDAY: u64 = 1_641_168_000_000_000_000;
+
+    #[test]
+    fn test_generate_schedule() {
+        let mut contract = setup_contract(accounts(1));
+        contract.add_break(at(12), at(13), true, None, None).unwrap();
+        // One-time break blocking Tuesday morning.
+        contract.add_break(at(9), at(12), false, Some(1_641_254_400_000), None).unwrap();
+
+        let long_task = contract.add_task("Long".to_string(), "".to_string(), 1, None, Some(DurationMinutes::from_hm(3, 0)), 10, None).unwrap();
+        let urgent_task = contract
+            .add_task("Urgent".to_string(), "".to_string(), 1, Some(MONDAY + NANOS_PER_DAY), Some(DurationMinutes::from_hm(2, 0)), 10, None)
+            .unwrap();
+        let huge_task = contract.add_task("Huge".to_string(), "".to_string(), 5, None, Some(DurationMinutes::from_hm(10, 0)), 10, None).unwrap();
+        let unestimated_task = contract.add_task("Vague".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        let full_day_task = contract.add_task("Full day".to_string(), "".to_string(), 1, None, Some(DurationMinutes::from_hm(6, 0)), 10, None).unwrap();
+        // Only fits on Tuesday, where the break pushes it past noon.
+        let short_task = contract.add_task("Short".to_string(), "".to_string(), 1, None, Some(DurationMinutes::from_hm(2, 0)), 10, None).unwrap();
+
+        let result = contract.generate_schedule(MONDAY, None).unwrap();
+        assert_eq!(
+            result.time_slots.monday,
+            vec![
+                TimeSlot { start_time: at(9), end_time: at(11), task_id: Some(urgent_task) },
+                TimeSlot { start_time: at(13), end_time: at(16), task_id: Some(long_task) },
+            ]
+        );
+        assert_eq!(result.time_slots.tuesday, vec![TimeSlot { start_time: at(13), end_time: at(15), task_id: Some(short_task) }]);
+        assert_eq!(result.time_slots.wednesday, vec![]);
+        assert_eq!(result.unscheduled_task_ids, vec![huge_task, unestimated_task, full_day_task]);
+        assert_eq!(contract.get_time_slots(accounts(1)), Some(result.time_slots));
+    }
+
+    #[test]
+    fn test_generate_schedule_in_local_time() {
+        let mut contract = setup_contract(accounts(1));
+        contract.set_utc_offset(2 * 60, None).unwrap();
+        let local_monday = MONDAY - 2 * 60 * NANOS_PER_MINUTE;
+        // One-time break on Tuesday morning, dated at local midnight.
+        contract.add_break(at(9), at(12), false, Some((local_monday + NANOS_PER_DAY) / NANOS_PER_MILLI), None).unwrap();
+
+        let urgent_task = contract
+            .add_task("Urgent".to_string(), "".to_string(), 1, Some(local_monday + 11 * 60 * NANOS_PER_MINUTE), Some(DurationMinutes::from_hm(2, 0)), 10, None)
+            .unwrap();
+        let rest_of_day_task = contract.add_task("Rest of day".to_string(), "".to_string(), 1, None, Some(DurationMinutes::from_hm(5, 0)), 10, None).unwrap();
+        let short_task = contract.add_task("Short".to_string(), "".to_string(), 1, None, Some(DurationMinutes::from_hm(2, 0)), 10, None).unwrap();
+
+        assert_eq!(contract.generate_schedule(MONDAY, None), Err(ContractError::InvalidWeekStart(MONDAY)));
+        let result = contract.generate_schedule(local_monday, None).unwrap();
+        assert_eq!(
+            result.time_slots.monday,
+            vec![
+                TimeSlot { start_time: at(9), end_time: at(11), task_id: Some(urgent_task) },
+                TimeSlot { start_time: at(11), end_time: at(16), task_id: Some(rest_of_day_task) },
+            ]
+        );
+        assert_eq!(result.time_slots.tuesday, vec![TimeSlot { start_time: at(12), end_time: at(14), task_id: Some(short_task) }]);
+        assert!(result.unscheduled_task_ids.is_empty());
+    }
+
+    #[test]
+    fn test_generate_schedule_requires_monday() {
+        let mut contract = setup_contract(accounts(1));
+
+        assert_eq!(
+            contract.generate_schedule(MONDAY + NANOS_PER_DAY, None),
+            Err(ContractError::InvalidWeekStart(MONDAY + NANOS_PER_DAY))
+        );
+    }
+}
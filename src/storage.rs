@@ -0,0 +1,526 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Balance, Promise, StorageUsage};
+use schemars::JsonSchema;
+
+use crate::workspace::is_workspace_id;
+use crate::{ContractError, PointsChange, RedemptionRequest, TodoListV1, TodoListV1Ext};
+
+/// Upper bound on the bytes taken by a registration record for the longest
+/// possible account ID. Its cost is the NEP-145 minimum deposit.
+pub const REGISTRATION_STORAGE_BYTES: StorageUsage = 250;
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, PartialEq, Debug)]
+pub struct AccountStorage {
+    deposit: Balance,
+    used_bytes: StorageUsage,
+}
+
+impl AccountStorage {
+    fn locked(&self) -> Balance {
+        Balance::from(self.used_bytes) * env::storage_byte_cost()
+    }
+
+    fn available(&self) -> Balance {
+        self.deposit.saturating_sub(self.locked())
+    }
+
+    fn balance(&self) -> StorageBalance {
+        StorageBalance {
+            total: U128(self.deposit),
+            available: U128(self.available()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+pub(crate) fn min_storage_balance() -> Balance {
+    Balance::from(REGISTRATION_STORAGE_BYTES) * env::storage_byte_cost()
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+
+        if let Some(account_storage) = self.storage_accounts.get_mut(&account_id) {
+            if registration_only {
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+            } else {
+                account_storage.deposit += amount;
+            }
+            return account_storage.balance();
+        }
+
+        if is_workspace_id(&account_id) {
+            env::panic_str(&format!("{} is a workspace ID, workspaces are registered by create_workspace", account_id));
+        }
+        let min_balance = min_storage_balance();
+        if amount < min_balance {
+            env::panic_str(&format!("A deposit of at least {} yoctoNEAR is required to register", min_balance));
+        }
+        let deposit = if registration_only {
+            let refund = amount - min_balance;
+            if refund > 0 {
+                Promise::new(env::predecessor_account_id()).transfer(refund);
+            }
+            min_balance
+        } else {
+            amount
+        };
+
+        self.register_account(account_id, deposit)
+    }
+
+    #[payable]
+    #[handle_result]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> Result<StorageBalance, ContractError> {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.withdraw_storage(&account_id, amount, account_id.clone())
+    }
+
+    /// Unregisters the caller and refunds their whole deposit. Accounts that
+    /// still own data must pass `force: true`, which deletes all of it. The
+    /// only owner of a workspace has to hand it over or delete it first.
+    #[payable]
+    #[handle_result]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> Result<bool, ContractError> {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        if !self.storage_accounts.contains_key(&account_id) {
+            return Ok(false);
+        }
+        if self.has_account_data(&account_id) && !force.unwrap_or(false) {
+            return Err(ContractError::AccountHasData);
+        }
+        for workspace_id in self.memberships.get(&account_id).cloned().unwrap_or_default() {
+            self.ensure_other_owner(&workspace_id, &account_id)?;
+        }
+        self.unregister_account(&account_id, account_id.clone());
+        Ok(true)
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(min_storage_balance()),
+            max: None,
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_accounts.get(&account_id).map(AccountStorage::balance)
+    }
+}
+
+impl TodoListV1 {
+    /// Checks that `account_id` can pay for storage and returns the usage to
+    /// pass to `settle_storage` once the call's changes are made.
+    pub(crate) fn begin_storage_tracking(&self, account_id: &AccountId) -> Result<StorageUsage, ContractError> {
+        if !self.storage_accounts.contains_key(account_id) {
+            return Err(ContractError::NotRegistered(account_id.clone()));
+        }
+        Ok(env::storage_usage())
+    }
+
+    /// Registers `account_id` with `deposit`, charging it for its own record.
+    pub(crate) fn register_account(&mut self, account_id: AccountId, deposit: Balance) -> StorageBalance {
+        let initial_storage_usage = env::storage_usage();
+        self.storage_accounts.insert(account_id.clone(), AccountStorage { deposit, used_bytes: 0 });
+        self.storage_accounts.flush();
+        let used_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        let account_storage = self.storage_accounts.get_mut(&account_id).unwrap_or_else(|| env::abort());
+        account_storage.used_bytes = used_bytes;
+        account_storage.balance()
+    }
+
+    /// Sends `amount`, or all of the account's available balance, to `recipient`.
+    pub(crate) fn withdraw_storage(&mut self, account_id: &AccountId, amount: Option<U128>, recipient: AccountId) -> Result<StorageBalance, ContractError> {
+        let account_storage = self
+            .storage_accounts
+            .get_mut(account_id)
+            .ok_or_else(|| ContractError::NotRegistered(account_id.clone()))?;
+
+        let available = account_storage.available();
+        let amount = amount.map_or(available, |amount| amount.0);
+        if amount > available {
+            return Err(ContractError::InsufficientStorageBalance { required: amount, available });
+        }
+        account_storage.deposit -= amount;
+        let balance = account_storage.balance();
+        if amount > 0 {
+            Promise::new(recipient).transfer(amount);
+        }
+        Ok(balance)
+    }
+
+    /// Deletes the account's data and registration and sends its whole
+    /// deposit to `recipient`.
+    pub(crate) fn unregister_account(&mut self, account_id: &AccountId, recipient: AccountId) {
+        if self.has_account_data(account_id) {
+            self.remove_account_data(account_id);
+        }
+        self.remove_account_references(account_id);
+        let account_storage = self.storage_accounts.remove(account_id).unwrap_or_else(|| env::abort());
+        if account_storage.deposit > 0 {
+            Promise::new(recipient).transfer(account_storage.deposit);
+        }
+    }
+
+    /// Registers an account whose data predates storage accounting. The data it
+    /// already has stays paid for by the contract.
+    pub(crate) fn register_existing_account(&mut self, account_id: AccountId) {
+        self.storage_accounts.entry(account_id).or_insert(AccountStorage { deposit: 0, used_bytes: 0 });
+    }
+
+    /// Attributes the bytes added or freed since `initial_storage_usage` to
+    /// `account_id`, failing if their deposit no longer covers them.
+    pub(crate) fn settle_storage(&mut self, account_id: &AccountId, initial_storage_usage: StorageUsage) -> Result<(), ContractError> {
+        self.flush_account_data(account_id);
+        let storage_usage = env::storage_usage();
+
+        let account_storage = self
+            .storage_accounts
+            .get_mut(account_id)
+            .ok_or_else(|| ContractError::NotRegistered(account_id.clone()))?;
+        account_storage.used_bytes = (account_storage.used_bytes + storage_usage).saturating_sub(initial_storage_usage);
+        let required = account_storage.locked();
+        if required > account_storage.deposit {
+            return Err(ContractError::InsufficientStorageBalance { required, available: account_storage.deposit });
+        }
+        Ok(())
+    }
+
+    /// Store collections cache writes until dropped, so they have to be written
+    /// out before `env::storage_usage` reflects the current call.
+    fn flush_account_data(&mut self, account_id: &AccountId) {
+        if let Some(tasks) = self.tasks.get_mut(account_id) {
+            tasks.flush();
+        }
+        if let Some(rewards) = self.rewards.get_mut(account_id) {
+            rewards.flush();
+        }
+        if let Some(days) = self.completed_tasks_per_day.get_mut(account_id) {
+            days.flush();
+        }
+        if let Some(months) = self.completed_tasks_per_month.get_mut(account_id) {
+            months.flush();
+        }
+        if let Some(grants) = self.access_grants.get_mut(account_id) {
+            grants.flush();
+        }
+        if let Some(projects) = self.projects.get_mut(account_id) {
+            projects.flush();
+        }
+        if let Some(tags) = self.tags.get_mut(account_id) {
+            tags.flush();
+        }
+        if let Some(members) = self.workspaces.get_mut(account_id) {
+            members.flush();
+        }
+        if let Some(token_ids) = self.nft_owner_tokens.get_mut(account_id) {
+            token_ids.flush();
+        }
+        if let Some(ledger) = self.points_ledger.get_mut(account_id) {
+            ledger.flush();
+        }
+        if let Some(requests) = self.redemption_requests.get_mut(account_id) {
+            requests.flush();
+        }
+        self.tasks.flush();
+        self.rewards.flush();
+        self.account_reward_points.flush();
+        self.next_task_ids.flush();
+        self.next_reward_ids.flush();
+        self.completed_tasks_per_day.flush();
+        self.working_hours.flush();
+        self.time_slots.flush();
+        self.breaks.flush();
+        self.access_grants.flush();
+        self.projects.flush();
+        self.next_project_ids.flush();
+        self.tags.flush();
+        self.next_tag_ids.flush();
+        self.workspaces.flush();
+        self.memberships.flush();
+        self.nft_tokens.flush();
+        self.nft_owner_tokens.flush();
+        self.milestones_reached.flush();
+        self.points_ledger.flush();
+        self.redemption_requests.flush();
+        self.next_redemption_ids.flush();
+        self.approver_requests.flush();
+        self.supervised_tasks.flush();
+        self.utc_offsets.flush();
+        self.completed_tasks_per_month.flush();
+        self.completed_tasks_total.flush();
+    }
+
+    fn has_account_data(&self, account_id: &AccountId) -> bool {
+        self.tasks.contains_key(account_id)
+            || self.rewards.contains_key(account_id)
+            || self.account_reward_points.contains_key(account_id)
+            || self.completed_tasks_per_day.contains_key(account_id)
+            || self.completed_tasks_per_month.contains_key(account_id)
+            || self.completed_tasks_total.contains_key(account_id)
+            || self.utc_offsets.contains_key(account_id)
+            || self.working_hours.contains_key(account_id)
+            || self.time_slots.contains_key(account_id)
+            || self.breaks.contains_key(account_id)
+            || self.access_grants.contains_key(account_id)
+            || self.projects.contains_key(account_id)
+            || self.tags.contains_key(account_id)
+            || self.workspaces.contains_key(account_id)
+            || self.nft_owner_tokens.contains_key(account_id)
+            || self.milestones_reached.contains_key(account_id)
+            || self.points_ledger.contains_key(account_id)
+            || self.redemption_requests.contains_key(account_id)
+    }
+
+    /// Deletes the account's data and the index entries pointing into it. The
+    /// ID sequences stay, so IDs are not handed out again if it registers anew.
+    fn remove_account_data(&mut self, account_id: &AccountId) {
+        if let Some(mut tasks) = self.tasks.remove(account_id) {
+            let supervised: Vec<(AccountId, u64)> =
+                tasks.values().filter_map(|task| Some((task.supervisor.clone()?.0, task.id))).collect();
+            for (supervisor_id, id) in supervised {
+                self.unindex_supervised_task(&supervisor_id, account_id, id);
+            }
+            tasks.clear();
+        }
+        if let Some(mut rewards) = self.rewards.remove(account_id) {
+            rewards.clear();
+        }
+        if let Some(mut days) = self.completed_tasks_per_day.remove(account_id) {
+            days.clear();
+        }
+        if let Some(mut months) = self.completed_tasks_per_month.remove(account_id) {
+            months.clear();
+        }
+        self.completed_tasks_total.remove(account_id);
+        self.utc_offsets.remove(account_id);
+        if let Some(mut grants) = self.access_grants.remove(account_id) {
+            grants.clear();
+        }
+        if let Some(mut projects) = self.projects.remove(account_id) {
+            projects.clear();
+        }
+        if let Some(mut tags) = self.tags.remove(account_id) {
+            tags.clear();
+        }
+        if let Some(mut members) = self.workspaces.remove(account_id) {
+            let member_ids: Vec<AccountId> = members.keys().cloned().collect();
+            for member_id in member_ids {
+                self.drop_membership(&member_id, account_id);
+            }
+            members.clear();
+        }
+        self.burn_badges(account_id);
+        // Escrowed points are forfeited.
+        if let Some(mut requests) = self.redemption_requests.remove(account_id) {
+            let pending: Vec<RedemptionRequest> = requests.values().cloned().collect();
+            for request in &pending {
+                self.unindex_redemption_request(request);
+            }
+            requests.clear();
+        }
+        // Burned rather than dropped so the total supply stays the sum of
+        // balances. Burning cannot overflow.
+        let reason = "Account unregistered".to_string();
+        let _ = self.change_points(account_id, -self.get_account_reward_points(account_id.clone()), PointsChange::Adjustment { reason });
+        self.account_reward_points.remove(account_id);
+        if let Some(mut ledger) = self.points_ledger.remove(account_id) {
+            ledger.clear();
+        }
+        self.working_hours.remove(account_id);
+        self.time_slots.remove(account_id);
+        self.breaks.remove(account_id);
+    }
+
+    /// Drops what other accounts keep about the account: its workspace
+    /// memberships, with the freed bytes credited to each workspace, and its
+    /// lists of requests to approve and tasks to supervise.
+    fn remove_account_references(&mut self, account_id: &AccountId) {
+        for workspace_id in self.memberships.remove(account_id).unwrap_or_default() {
+            let initial_storage_usage = env::storage_usage();
+            if let Some(members) = self.workspaces.get_mut(&workspace_id) {
+                members.remove(account_id);
+            }
+            let _ = self.settle_storage(&workspace_id, initial_storage_usage);
+        }
+        self.approver_requests.remove(account_id);
+        self.supervised_tasks.remove(account_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use crate::{Permission, Role};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use near_sdk::ONE_NEAR;
+
+    #[test]
+    fn test_storage_deposit_registration_only() {
+        testing_env!(get_context(accounts(1)).attached_deposit(ONE_NEAR).build());
+        let mut contract = TodoListV1::new();
+
+        let balance = contract.storage_deposit(None, Some(true));
+        let min = contract.storage_balance_bounds().min;
+        assert_eq!(balance.total, min);
+        assert!(balance.available.0 > 0);
+        assert_eq!(contract.storage_balance_of(accounts(1)), Some(balance));
+        assert_eq!(contract.storage_balance_of(accounts(2)), None);
+    }
+
+    #[test]
+    fn test_mutating_methods_charge_storage() {
+        let mut contract = setup_contract(accounts(1));
+        let initial_available = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+
+        let task_id = contract.add_task("Test Task".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        let after_add = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+        assert!(after_add < initial_available);
+
+        contract.remove_task(task_id, None).unwrap();
+        let after_remove = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+        assert!(after_remove > after_add);
+    }
+
+    #[test]
+    fn test_unregistered_account_cannot_add_data() {
+        testing_env!(get_context(accounts(1)).build());
+        let mut contract = TodoListV1::new();
+
+        assert_eq!(
+            contract.add_task("Test Task".to_string(), "".to_string(), 1, None, None, 10, None),
+            Err(ContractError::NotRegistered(accounts(1)))
+        );
+    }
+
+    #[test]
+    fn test_insufficient_storage_balance() {
+        testing_env!(get_context(accounts(1)).attached_deposit(ONE_NEAR).build());
+        let mut contract = TodoListV1::new();
+        contract.storage_deposit(None, Some(true));
+        testing_env!(get_context(accounts(1)).build());
+
+        let result = contract.add_task("Test Task".to_string(), "x".repeat(500), 1, None, None, 10, None);
+        assert!(matches!(result, Err(ContractError::InsufficientStorageBalance { .. })));
+    }
+
+    #[test]
+    fn test_storage_unregister() {
+        let mut contract = setup_contract(accounts(1));
+        contract.add_task("Test Task".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        assert_eq!(contract.storage_unregister(None), Err(ContractError::AccountHasData));
+        assert_eq!(contract.storage_unregister(Some(true)), Ok(true));
+        assert!(contract.get_tasks(accounts(1)).is_empty());
+        assert_eq!(contract.storage_balance_of(accounts(1)), None);
+        assert_eq!(contract.storage_unregister(None), Ok(false));
+    }
+
+    #[test]
+    fn test_writes_to_other_accounts_are_charged() {
+        let mut contract = setup_contract(accounts(1));
+        testing_env!(get_context(accounts(1)).attached_deposit(ONE_NEAR).build());
+        let team = contract.create_workspace("team".to_string()).unwrap();
+        testing_env!(get_context(accounts(1)).build());
+        let task = contract.add_task("Shared".to_string(), "".to_string(), 1, None, None, 10, Some(team.clone())).unwrap();
+        contract.assign_task(task, Some(accounts(1)), Some(team.clone())).unwrap();
+
+        // Bob's ledger entry is written by a call the workspace pays for.
+        let available = contract.storage_balance_of(team.clone()).unwrap().available.0;
+        let initial_storage_usage = env::storage_usage();
+        contract.mark_complete(task, Some(team.clone())).unwrap();
+        let charged = available - contract.storage_balance_of(team).unwrap().available.0;
+        drop(contract);
+        assert_eq!(charged, Balance::from(env::storage_usage() - initial_storage_usage) * env::storage_byte_cost());
+    }
+
+    #[test]
+    fn test_unregister_removes_references_to_the_account() {
+        let mut contract = setup_contract(accounts(1));
+        testing_env!(get_context(accounts(2)).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+        testing_env!(get_context(accounts(1)).attached_deposit(ONE_NEAR).build());
+        let team = contract.create_workspace("team".to_string()).unwrap();
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_member_role(team.clone(), accounts(2), Role::Editor).unwrap();
+        contract.grant_access(accounts(2), vec![Permission::ManageTasks]).unwrap();
+        contract.adjust_points(accounts(1), 50, "Allowance".to_string()).unwrap();
+        let reward = contract.add_reward("Reward".to_string(), "".to_string(), 10, None).unwrap();
+        contract.set_reward_approver(reward, Some(accounts(2)), None).unwrap();
+        contract.request_redemption(reward, None).unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        contract.add_supervised_task("Task".to_string(), "".to_string(), 1, None, None, 10, accounts(1)).unwrap();
+
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        assert_eq!(contract.storage_unregister(Some(true)), Err(ContractError::LastWorkspaceOwner));
+        testing_env!(get_context(accounts(2)).attached_deposit(1).build());
+        assert_eq!(contract.storage_unregister(None), Ok(true));
+        assert!(contract.supervised_tasks.get(&accounts(2)).is_none());
+        assert!(contract.approver_requests.get(&accounts(2)).is_none());
+        assert!(contract.get_workspaces(accounts(2)).is_empty());
+        assert_eq!(contract.get_workspace_members(team).len(), 1);
+    }
+
+    #[test]
+    fn test_unregister_keeps_ids_and_cleans_indexes() {
+        let mut contract = setup_contract(accounts(1));
+        contract.grant_access(accounts(2), vec![Permission::ManageTasks]).unwrap();
+        contract.adjust_points(accounts(1), 50, "Allowance".to_string()).unwrap();
+        let reward = contract.add_reward("Reward".to_string(), "".to_string(), 10, None).unwrap();
+        contract.set_reward_approver(reward, Some(accounts(2)), None).unwrap();
+        contract.request_redemption(reward, None).unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        let task = contract.add_supervised_task("Task".to_string(), "".to_string(), 1, None, None, 10, accounts(1)).unwrap();
+
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        assert_eq!(contract.storage_unregister(Some(true)), Ok(true));
+        assert!(contract.supervised_tasks.get(&accounts(2)).is_none());
+        assert!(contract.approver_requests.get(&accounts(2)).is_none());
+
+        testing_env!(get_context(accounts(1)).attached_deposit(ONE_NEAR).build());
+        contract.storage_deposit(None, None);
+        testing_env!(get_context(accounts(1)).build());
+        let next_task = contract.add_task("Task".to_string(), "".to_string(), 1, None, None, 10, None).unwrap();
+        assert!(next_task > task);
+        assert!(contract.add_reward("Reward".to_string(), "".to_string(), 10, None).unwrap() > reward);
+    }
+
+    #[test]
+    fn test_storage_withdraw() {
+        let mut contract = setup_contract(accounts(1));
+        let available = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        let balance = contract.storage_withdraw(Some(U128(available / 2))).unwrap();
+        assert_eq!(balance.available.0, available - available / 2);
+        assert!(matches!(
+            contract.storage_withdraw(Some(U128(available))),
+            Err(ContractError::InsufficientStorageBalance { .. })
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{ContractError, DurationMinutes, Permission, Task, TodoEvent, TodoListV1, TodoListV1Ext};
+
+/// A supervised task handed in for verification.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct Submission {
+    submitted_at: u64,
+    /// Free-form note or URL showing the work was done.
+    proof: Option<String>,
+}
+
+/// Supervised tasks are created by a supervisor in another account's list.
+/// The account submits them instead of calling `mark_complete`, and their
+/// points are credited only once the supervisor verifies the submission.
+#[near_bindgen]
+impl TodoListV1 {
+    /// Creates a task in `account_id`'s list with the caller as supervisor and
+    /// returns its ID. The caller needs `ManageTasks` on that account, and only
+    /// the caller can change the task afterwards.
+    #[handle_result]
+    pub fn add_supervised_task(&mut self, title: String, description: String, priority: u8, deadline: Option<u64>,
+                               estimated_time: Option<DurationMinutes>, reward_points: i64, account_id: AccountId) -> Result<u64, ContractError> {
+        let supervisor_id = env::predecessor_account_id();
+        if account_id == supervisor_id {
+            return Err(ContractError::Unauthorized);
+        }
+        let account_id = self.authorize(Some(account_id), Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let id = self.create_task(&account_id, title, description, priority, deadline, estimated_time, reward_points, Some(supervisor_id))?;
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(id)
+    }
+
+    /// Hands a supervised task in for verification, replacing any earlier
+    /// submission that has not been reviewed yet.
+    #[handle_result]
+    pub fn submit_task(&mut self, id: u64, proof: Option<String>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::CompleteTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        if let Some(proof) = &proof {
+            self.limits.validate_proof(proof)?;
+        }
+
+        let task = self.get_task(account_id.clone(), id).ok_or(ContractError::TaskNotFound(id))?;
+        if task.supervisor.is_none() {
+            return Err(ContractError::NotSupervised(id));
+        }
+        if task.completed {
+            return Err(ContractError::AlreadyCompleted(id));
+        }
+        let pending = self.pending_dependencies(&account_id, &task);
+        if !pending.is_empty() {
+            return Err(ContractError::DependenciesIncomplete(pending));
+        }
+        TodoEvent::TaskSubmitted { account_id: &account_id, task_id: id, proof: proof.as_deref() }.emit();
+        self.task_mut(&account_id, id)?.submission = Some(Submission { submitted_at: env::block_timestamp(), proof });
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Accepts the submission, which completes the task like `mark_complete`
+    /// and credits its points. Only the task's supervisor can verify it.
+    #[handle_result]
+    pub fn verify_task(&mut self, account_id: AccountId, id: u64) -> Result<Option<u64>, ContractError> {
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        let supervisor_id = self.submitted_task_for_supervisor(&account_id, id)?;
+
+        TodoEvent::TaskVerified { account_id: &account_id, task_id: id, supervisor_id: &supervisor_id }.emit();
+        let next_id = self.complete_task(&account_id, id)?;
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(next_id)
+    }
+
+    /// Turns the submission down and sends the task back to open. Only the
+    /// task's supervisor can reject it.
+    #[handle_result]
+    pub fn reject_task(&mut self, account_id: AccountId, id: u64, reason: Option<String>) -> Result<(), ContractError> {
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        self.submitted_task_for_supervisor(&account_id, id)?;
+        if let Some(reason) = &reason {
+            self.limits.validate_proof(reason)?;
+        }
+
+        TodoEvent::TaskSubmissionRejected { account_id: &account_id, task_id: id, reason: reason.as_deref() }.emit();
+        self.task_mut(&account_id, id)?.submission = None;
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Open tasks supervised by `supervisor_id`, across all accounts. Submitted
+    /// ones are waiting for verification. Verified tasks drop out of the list.
+    pub fn get_supervised_tasks(&self, supervisor_id: AccountId) -> Vec<Task> {
+        self.supervised_tasks
+            .get(&supervisor_id)
+            .map(|tasks| {
+                tasks
+                    .iter()
+                    .filter_map(|(account_id, id)| self.tasks.get(account_id)?.get(id))
+                    .filter(|task| !task.completed)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl TodoListV1 {
+    /// Returns the caller if it supervises the task and the task is waiting
+    /// for verification.
+    fn submitted_task_for_supervisor(&self, account_id: &AccountId, id: u64) -> Result<AccountId, ContractError> {
+        let task = self.get_task(account_id.clone(), id).ok_or(ContractError::TaskNotFound(id))?;
+        let supervisor_id = task.supervisor.ok_or(ContractError::NotSupervised(id))?.0;
+        if supervisor_id != env::predecessor_account_id() {
+            return Err(ContractError::Unauthorized);
+        }
+        if task.completed {
+            return Err(ContractError::AlreadyCompleted(id));
+        }
+        if task.submission.is_none() {
+            return Err(ContractError::NotSubmitted(id));
+        }
+        Ok(supervisor_id)
+    }
+
+    pub(crate) fn index_supervised_task(&mut self, supervisor_id: &AccountId, account_id: &AccountId, id: u64) {
+        self.supervised_tasks.entry(supervisor_id.clone()).or_default().push((account_id.clone(), id));
+    }
+
+    pub(crate) fn unindex_supervised_task(&mut self, supervisor_id: &AccountId, account_id: &AccountId, id: u64) {
+        let now_empty = self.supervised_tasks.get_mut(supervisor_id).is_some_and(|tasks| {
+            tasks.retain(|(owner_id, task_id)| !(owner_id == account_id && *task_id == id));
+            tasks.is_empty()
+        });
+        if now_empty {
+            self.supervised_tasks.remove(supervisor_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    /// Bob lets Charlie supervise his list, and Charlie adds a task worth 20.
+    fn setup_supervised_task(contract: &mut TodoListV1) -> u64 {
+        contract.grant_access(accounts(2), vec![Permission::ManageTasks]).unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        let id = contract.add_supervised_task("Homework".to_string(), "".to_string(), 1, None, None, 20, accounts(1)).unwrap();
+        testing_env!(get_context(accounts(1)).build());
+        id
+    }
+
+    #[test]
+    fn test_submit_and_verify() {
+        let mut contract = setup_contract(accounts(1));
+        let id = setup_supervised_task(&mut contract);
+        assert_eq!(contract.mark_complete(id, None), Err(ContractError::VerificationRequired(id)));
+        assert_eq!(contract.update_task(id, "Easy".to_string(), "".to_string(), 1, None, None, 500, None), Err(ContractError::Unauthorized));
+        assert_eq!(contract.verify_task(accounts(1), id), Err(ContractError::Unauthorized));
+
+        contract.submit_task(id, Some("https://example.com/essay".to_string()), None).unwrap();
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 0);
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(contract.get_supervised_tasks(accounts(2)).len(), 1);
+        contract.verify_task(accounts(1), id).unwrap();
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 20);
+        assert!(contract.get_supervised_tasks(accounts(2)).is_empty());
+        assert!(contract.supervised_tasks.get(&accounts(2)).is_none());
+        assert_eq!(contract.verify_task(accounts(1), id), Err(ContractError::AlreadyCompleted(id)));
+    }
+
+    #[test]
+    fn test_verified_task_cannot_be_reopened() {
+        let mut contract = setup_contract(accounts(1));
+        let id = setup_supervised_task(&mut contract);
+        contract.submit_task(id, None, None).unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        contract.verify_task(accounts(1), id).unwrap();
+
+        testing_env!(get_context(accounts(1)).build());
+        assert_eq!(contract.mark_incomplete(id, None), Err(ContractError::VerifiedTask(id)));
+        assert_eq!(contract.get_task(accounts(1), id).unwrap().submission, None);
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(contract.verify_task(accounts(1), id), Err(ContractError::AlreadyCompleted(id)));
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 20);
+    }
+
+    #[test]
+    fn test_reject_reopens_task() {
+        let mut contract = setup_contract(accounts(1));
+        let id = setup_supervised_task(&mut contract);
+        let own = contract.add_task("Own".to_string(), "".to_string(), 1, None, None, 5, None).unwrap();
+        assert_eq!(contract.submit_task(own, None, None), Err(ContractError::NotSupervised(own)));
+        contract.submit_task(id, None, None).unwrap();
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.reject_task(accounts(1), id, Some("Show your work".to_string())).unwrap();
+        assert_eq!(contract.verify_task(accounts(1), id), Err(ContractError::NotSubmitted(id)));
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 0);
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.remove_task(id, None).unwrap();
+        assert!(contract.get_supervised_tasks(accounts(2)).is_empty());
+    }
+}
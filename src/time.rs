@@ -0,0 +1,280 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::de::{self, Deserializer};
+use near_sdk::serde::ser::Serializer;
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fmt;
+use std::str::FromStr;
+
+pub const MINUTES_PER_HOUR: u16 = 60;
+pub const MINUTES_PER_DAY: u16 = 24 * MINUTES_PER_HOUR;
+pub const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+const NANOS_PER_MINUTE: i128 = 60_000_000_000;
+/// Offsets from UTC in use around the world, from UTC-12:00 to UTC+14:00.
+pub const MIN_UTC_OFFSET: i16 = -12 * 60;
+pub const MAX_UTC_OFFSET: i16 = 14 * 60;
+/// 1970-01-01 was a Thursday, three days after the Monday that starts a week.
+const EPOCH_WEEKDAY: u64 = 3;
+
+/// Day of the week of a day counted from the Unix epoch, with Monday as 0.
+pub(crate) fn weekday_index(day: u64) -> u64 {
+    (day + EPOCH_WEEKDAY) % 7
+}
+
+/// Local day, counted from the Unix epoch, of a block timestamp at
+/// `utc_offset` minutes from UTC.
+pub(crate) fn local_day(timestamp: u64, utc_offset: i16) -> u32 {
+    let local = i128::from(timestamp) + i128::from(utc_offset) * NANOS_PER_MINUTE;
+    (local.max(0) / i128::from(NANOS_PER_DAY)).min(i128::from(u32::MAX)) as u32
+}
+
+/// Block timestamp at which the local `day` starts, the inverse of `local_day`.
+pub(crate) fn local_day_start(day: u32, utc_offset: i16) -> u64 {
+    let start = i128::from(day) * i128::from(NANOS_PER_DAY) - i128::from(utc_offset) * NANOS_PER_MINUTE;
+    start.max(0) as u64
+}
+
+/// Months counted from year 0, so that consecutive months have consecutive
+/// indexes.
+pub(crate) fn month_index(day: u32) -> u32 {
+    let (year, month, _) = civil_from_days(u64::from(day));
+    year as u32 * 12 + month - 1
+}
+
+/// Inverse of `month_index`, as `(year, month)`.
+pub(crate) fn month_from_index(index: u32) -> (u32, u32) {
+    (index / 12, index % 12 + 1)
+}
+
+/// Converts a day counted from the Unix epoch to a UTC `(year, month, day)`.
+pub(crate) fn civil_from_days(days: u64) -> (u64, u32, u32) {
+    // Howard Hinnant's algorithm, with eras of 400 years starting in March.
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Inverse of `civil_from_days`.
+pub(crate) fn days_from_civil(year: u64, month: u32, day: u32) -> u64 {
+    let year = year - u64::from(month <= 2);
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let shifted_month = u64::from(if month > 2 { month - 3 } else { month + 9 });
+    let day_of_year = (153 * shifted_month + 2) / 5 + u64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub(crate) fn days_in_month(year: u64, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Minutes since midnight, from `00:00` up to and including `24:00` so that a
+/// range can end at the end of the day. Serialized to JSON as `"HH:MM"`.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct TimeOfDay(u16);
+
+/// A length of time in whole minutes. Serialized to JSON as `"HH:MM"`, where
+/// the hours may exceed 24.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct DurationMinutes(u32);
+
+impl TimeOfDay {
+    pub const MIDNIGHT: Self = Self(0);
+    pub const END_OF_DAY: Self = Self(MINUTES_PER_DAY);
+
+    /// Panics on values past `24:00`, so it is meant for constants.
+    pub const fn from_hm(hours: u16, minutes: u16) -> Self {
+        assert!(minutes < MINUTES_PER_HOUR && hours * MINUTES_PER_HOUR + minutes <= MINUTES_PER_DAY);
+        Self(hours * MINUTES_PER_HOUR + minutes)
+    }
+
+    pub fn from_minutes(minutes: u16) -> Option<Self> {
+        (minutes <= MINUTES_PER_DAY).then_some(Self(minutes))
+    }
+
+    pub fn minutes(self) -> u16 {
+        self.0
+    }
+
+    /// Converts the fractional hours stored by earlier versions, rounding to the
+    /// nearest minute and clamping into the day.
+    pub(crate) fn from_legacy_hours(hours: f64) -> Self {
+        let minutes = (hours * f64::from(MINUTES_PER_HOUR)).round();
+        if minutes.is_nan() {
+            return Self::MIDNIGHT;
+        }
+        Self(minutes.clamp(0.0, f64::from(MINUTES_PER_DAY)) as u16)
+    }
+}
+
+impl DurationMinutes {
+    pub const fn from_hm(hours: u32, minutes: u32) -> Self {
+        assert!(minutes < MINUTES_PER_HOUR as u32);
+        Self(hours * MINUTES_PER_HOUR as u32 + minutes)
+    }
+
+    pub const fn from_minutes(minutes: u32) -> Self {
+        Self(minutes)
+    }
+
+    pub fn minutes(self) -> u32 {
+        self.0
+    }
+
+    /// Converts the fractional hours stored by earlier versions. Values that do
+    /// not round to at least one minute yield `None`.
+    pub(crate) fn from_legacy_hours(hours: f64) -> Option<Self> {
+        let minutes = (hours * f64::from(MINUTES_PER_HOUR)).round();
+        (minutes >= 1.0).then(|| Self(minutes.min(f64::from(u32::MAX)) as u32))
+    }
+}
+
+/// Parses `"HH:MM"` into a number of minutes. Minutes must be two digits below 60.
+fn parse_hm(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.split_once(':')?;
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    if !is_digits(hours) || minutes.len() != 2 || !is_digits(minutes) {
+        return None;
+    }
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    if minutes >= u32::from(MINUTES_PER_HOUR) {
+        return None;
+    }
+    hours.checked_mul(u32::from(MINUTES_PER_HOUR))?.checked_add(minutes)
+}
+
+impl FromStr for TimeOfDay {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_hm(value)
+            .and_then(|minutes| u16::try_from(minutes).ok())
+            .and_then(TimeOfDay::from_minutes)
+            .ok_or_else(|| format!("invalid time of day {:?}, expected HH:MM between 00:00 and 24:00", value))
+    }
+}
+
+impl FromStr for DurationMinutes {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_hm(value)
+            .map(DurationMinutes)
+            .ok_or_else(|| format!("invalid duration {:?}, expected HH:MM", value))
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / MINUTES_PER_HOUR, self.0 % MINUTES_PER_HOUR)
+    }
+}
+
+impl fmt::Display for DurationMinutes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes_per_hour = u32::from(MINUTES_PER_HOUR);
+        write!(f, "{:02}:{:02}", self.0 / minutes_per_hour, self.0 % minutes_per_hour)
+    }
+}
+
+macro_rules! impl_hm_serde {
+    ($type:ty) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <String as Deserialize>::deserialize(deserializer)?;
+                value.parse().map_err(de::Error::custom)
+            }
+        }
+
+        impl JsonSchema for $type {
+            fn schema_name() -> String {
+                stringify!($type).to_owned()
+            }
+
+            fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+                <String>::json_schema(gen)
+            }
+        }
+    };
+}
+
+impl_hm_serde!(TimeOfDay);
+impl_hm_serde!(DurationMinutes);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::serde_json;
+
+    #[test]
+    fn test_time_of_day_json() {
+        assert_eq!(serde_json::to_string(&TimeOfDay::from_hm(9, 5)).unwrap(), r#""09:05""#);
+        assert_eq!(serde_json::from_str::<TimeOfDay>(r#""24:00""#).unwrap(), TimeOfDay::END_OF_DAY);
+        assert_eq!(serde_json::from_str::<TimeOfDay>(r#""7:30""#).unwrap(), TimeOfDay::from_hm(7, 30));
+        for invalid in [r#""24:01""#, r#""12:60""#, r#""12:5""#, r#""-1:00""#, r#""noon""#, "12.5"] {
+            assert!(serde_json::from_str::<TimeOfDay>(invalid).is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn test_duration_json() {
+        assert_eq!(serde_json::to_string(&DurationMinutes::from_hm(26, 0)).unwrap(), r#""26:00""#);
+        assert_eq!(serde_json::from_str::<DurationMinutes>(r#""01:30""#).unwrap(), DurationMinutes::from_minutes(90));
+        assert!(serde_json::from_str::<DurationMinutes>(r#""1:3""#).is_err());
+    }
+
+    #[test]
+    fn test_civil_dates() {
+        // 2022-01-03, a Monday.
+        assert_eq!(civil_from_days(18_995), (2022, 1, 3));
+        assert_eq!(days_from_civil(2022, 1, 3), 18_995);
+        assert_eq!(weekday_index(18_995), 0);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+    }
+
+    #[test]
+    fn test_local_days() {
+        // 2022-01-03 22:00 UTC is already the next day at UTC+02:00.
+        let timestamp = 18_995 * NANOS_PER_DAY + 22 * 3_600_000_000_000;
+        assert_eq!(local_day(timestamp, 0), 18_995);
+        assert_eq!(local_day(timestamp, 120), 18_996);
+        assert_eq!(local_day(timestamp, MIN_UTC_OFFSET), 18_995);
+        assert_eq!(local_day_start(18_996, 120), 18_995 * NANOS_PER_DAY + 22 * 3_600_000_000_000);
+        assert_eq!(local_day(0, MIN_UTC_OFFSET), 0);
+        assert_eq!(month_from_index(month_index(18_995)), (2022, 1));
+        assert_eq!(month_index(days_from_civil(2022, 2, 1) as u32), month_index(18_995) + 1);
+    }
+
+    #[test]
+    fn test_from_legacy_hours() {
+        assert_eq!(TimeOfDay::from_legacy_hours(9.5), TimeOfDay::from_hm(9, 30));
+        assert_eq!(TimeOfDay::from_legacy_hours(30.0), TimeOfDay::END_OF_DAY);
+        assert_eq!(TimeOfDay::from_legacy_hours(f64::NAN), TimeOfDay::MIDNIGHT);
+        assert_eq!(DurationMinutes::from_legacy_hours(1.25), Some(DurationMinutes::from_minutes(75)));
+        assert_eq!(DurationMinutes::from_legacy_hours(0.0), None);
+        assert_eq!(DurationMinutes::from_legacy_hours(f64::NAN), None);
+    }
+}
@@ -0,0 +1,241 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::{ContractError, DurationMinutes, TimeOfDay, TimeSlot, TimeSlots, WeeklyWorkingHours, WorkingHours};
+
+/// Input limits enforced on every mutating method. Adjustable by the contract
+/// account through `set_validation_limits`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct ValidationLimits {
+    pub max_title_length: u32,
+    pub max_description_length: u32,
+    pub min_priority: u8,
+    pub max_priority: u8,
+    pub max_tasks_per_account: u32,
+    /// Largest `reward_points` a task can award, and the largest single
+    /// adjustment by the contract owner.
+    pub max_reward_points: i64,
+    pub max_cost: i64,
+}
+
+impl Default for ValidationLimits {
+    fn default() -> Self {
+        Self {
+            max_title_length: 100,
+            max_description_length: 1000,
+            min_priority: 1,
+            max_priority: 5,
+            max_tasks_per_account: 500,
+            max_reward_points: 1_000_000,
+            max_cost: 1_000_000,
+        }
+    }
+}
+
+impl ValidationLimits {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.min_priority > self.max_priority {
+            return Err(ContractError::InvalidLimits("min_priority must not exceed max_priority"));
+        }
+        if self.max_title_length == 0 || self.max_tasks_per_account == 0 {
+            return Err(ContractError::InvalidLimits("title length and task count limits must be positive"));
+        }
+        if self.max_reward_points < 0 || self.max_cost < 0 {
+            return Err(ContractError::InvalidLimits("point and cost limits must not be negative"));
+        }
+        Ok(())
+    }
+
+    pub fn validate_task(
+        &self,
+        title: &str,
+        description: &str,
+        priority: u8,
+        estimated_time: Option<DurationMinutes>,
+        reward_points: i64,
+    ) -> Result<(), ContractError> {
+        self.validate_text(title, description)?;
+        if priority < self.min_priority || priority > self.max_priority {
+            return Err(ContractError::PriorityOutOfRange { priority, min: self.min_priority, max: self.max_priority });
+        }
+        if let Some(duration) = estimated_time.filter(|duration| duration.minutes() == 0) {
+            return Err(ContractError::InvalidDuration(duration));
+        }
+        validate_non_negative("reward_points", reward_points)?;
+        validate_at_most("reward_points", reward_points, self.max_reward_points)
+    }
+
+    pub fn validate_reward(&self, title: &str, description: &str, cost: i64) -> Result<(), ContractError> {
+        self.validate_text(title, description)?;
+        validate_non_negative("cost", cost)?;
+        validate_at_most("cost", cost, self.max_cost)
+    }
+
+    /// Adjustments may debit as well as credit, up to `max_reward_points` either way.
+    pub fn validate_adjustment(&self, amount: i64) -> Result<(), ContractError> {
+        validate_at_most("amount", amount.saturating_abs(), self.max_reward_points)
+    }
+
+    pub fn validate_task_count(&self, task_count: u32) -> Result<(), ContractError> {
+        if task_count >= self.max_tasks_per_account {
+            return Err(ContractError::TaskLimitReached { max: self.max_tasks_per_account });
+        }
+        Ok(())
+    }
+
+    /// Project and tag names share the title length limit.
+    pub fn validate_label(&self, name: &str, color: Option<&str>) -> Result<(), ContractError> {
+        if name.trim().is_empty() {
+            return Err(ContractError::EmptyName);
+        }
+        if name.chars().count() > self.max_title_length as usize {
+            return Err(ContractError::TextTooLong { field: "name", max: self.max_title_length });
+        }
+        if let Some(color) = color {
+            let is_hex_color = color.len() == 7
+                && color.starts_with('#')
+                && color[1..].bytes().all(|byte| byte.is_ascii_hexdigit());
+            if !is_hex_color {
+                return Err(ContractError::InvalidColor(color.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Proofs submitted with supervised tasks share the description length limit.
+    pub fn validate_proof(&self, proof: &str) -> Result<(), ContractError> {
+        if proof.chars().count() > self.max_description_length as usize {
+            return Err(ContractError::TextTooLong { field: "proof", max: self.max_description_length });
+        }
+        Ok(())
+    }
+
+    fn validate_text(&self, title: &str, description: &str) -> Result<(), ContractError> {
+        if title.trim().is_empty() {
+            return Err(ContractError::EmptyTitle);
+        }
+        if title.chars().count() > self.max_title_length as usize {
+            return Err(ContractError::TextTooLong { field: "title", max: self.max_title_length });
+        }
+        if description.chars().count() > self.max_description_length as usize {
+            return Err(ContractError::TextTooLong { field: "description", max: self.max_description_length });
+        }
+        Ok(())
+    }
+}
+
+fn validate_non_negative(field: &'static str, value: i64) -> Result<(), ContractError> {
+    if value < 0 {
+        return Err(ContractError::NegativeValue { field, value });
+    }
+    Ok(())
+}
+
+fn validate_at_most(field: &'static str, value: i64, max: i64) -> Result<(), ContractError> {
+    if value > max {
+        return Err(ContractError::ValueTooLarge { field, value, max });
+    }
+    Ok(())
+}
+
+/// Checks that `start_time..end_time` is a non-empty range. `TimeOfDay` already
+/// keeps both ends within the day.
+pub fn validate_time_range(start_time: TimeOfDay, end_time: TimeOfDay) -> Result<(), ContractError> {
+    if start_time >= end_time {
+        return Err(ContractError::InvalidTimeRange { start_time, end_time });
+    }
+    Ok(())
+}
+
+/// Working hours may be empty (`start_time == end_time`) to mark a day off.
+pub fn validate_working_hours(working_hours: &WeeklyWorkingHours) -> Result<(), ContractError> {
+    for WorkingHours { start_time, end_time } in working_hours.days() {
+        if start_time != end_time {
+            validate_time_range(*start_time, *end_time)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_time_slots(time_slots: &TimeSlots) -> Result<(), ContractError> {
+    for slots in time_slots.days() {
+        for TimeSlot { start_time, end_time, .. } in slots {
+            validate_time_range(*start_time, *end_time)?;
+        }
+
+        let mut ranges: Vec<(TimeOfDay, TimeOfDay)> = slots.iter().map(|slot| (slot.start_time, slot.end_time)).collect();
+        ranges.sort();
+        if ranges.windows(2).any(|pair| pair[1].0 < pair[0].1) {
+            return Err(ContractError::OverlappingTimeSlots);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::at;
+
+    #[test]
+    fn test_validate_task() {
+        let limits = ValidationLimits::default();
+
+        assert!(limits.validate_task("Title", "", 3, Some(DurationMinutes::from_hm(1, 30)), 10).is_ok());
+        assert_eq!(limits.validate_task("  ", "", 3, None, 10), Err(ContractError::EmptyTitle));
+        assert_eq!(
+            limits.validate_task(&"x".repeat(101), "", 3, None, 10),
+            Err(ContractError::TextTooLong { field: "title", max: 100 })
+        );
+        assert_eq!(
+            limits.validate_task("Title", "", 9, None, 10),
+            Err(ContractError::PriorityOutOfRange { priority: 9, min: 1, max: 5 })
+        );
+        assert_eq!(
+            limits.validate_task("Title", "", 3, Some(DurationMinutes::from_minutes(0)), 10),
+            Err(ContractError::InvalidDuration(DurationMinutes::from_minutes(0)))
+        );
+        assert_eq!(
+            limits.validate_task("Title", "", 3, None, -1),
+            Err(ContractError::NegativeValue { field: "reward_points", value: -1 })
+        );
+        assert_eq!(
+            limits.validate_task("Title", "", 3, None, i64::MAX),
+            Err(ContractError::ValueTooLarge { field: "reward_points", value: i64::MAX, max: 1_000_000 })
+        );
+        assert_eq!(
+            limits.validate_reward("Title", "", 1_000_001),
+            Err(ContractError::ValueTooLarge { field: "cost", value: 1_000_001, max: 1_000_000 })
+        );
+        assert_eq!(
+            limits.validate_adjustment(i64::MIN),
+            Err(ContractError::ValueTooLarge { field: "amount", value: i64::MAX, max: 1_000_000 })
+        );
+    }
+
+    #[test]
+    fn test_validate_time_range() {
+        assert!(validate_time_range(TimeOfDay::MIDNIGHT, TimeOfDay::END_OF_DAY).is_ok());
+        assert!(validate_time_range(at(10), at(9)).is_err());
+        assert!(validate_time_range(at(9), at(9)).is_err());
+    }
+
+    #[test]
+    fn test_validate_time_slots_overlap() {
+        let slot = |start_hour, end_hour| TimeSlot { start_time: at(start_hour), end_time: at(end_hour), task_id: None };
+        let mut time_slots = TimeSlots {
+            monday: vec![slot(9, 10), slot(10, 11)],
+            tuesday: vec![],
+            wednesday: vec![],
+            thursday: vec![],
+            friday: vec![],
+            saturday: vec![],
+            sunday: vec![],
+        };
+        assert!(validate_time_slots(&time_slots).is_ok());
+
+        time_slots.friday = vec![slot(13, 15), slot(12, 14)];
+        assert_eq!(validate_time_slots(&time_slots), Err(ContractError::OverlappingTimeSlots));
+    }
+}
@@ -0,0 +1,366 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::UnorderedMap;
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::storage::{min_storage_balance, StorageBalance};
+use crate::{account_hash, AccountIdWrapper, ContractError, Permission, StorageKey, Task, TodoEvent, TodoListV1, TodoListV1Ext};
+
+/// What a member may do in a workspace. Anyone can read it through the views.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Everything, including managing members, rewards and the workspace points.
+    Owner,
+    /// Manages and completes tasks and the schedule.
+    Editor,
+    Viewer,
+}
+
+impl Role {
+    fn allows(self, permission: Permission) -> bool {
+        match self {
+            Role::Owner => true,
+            Role::Editor => matches!(permission, Permission::ManageTasks | Permission::CompleteTasks | Permission::ManageSchedule),
+            Role::Viewer => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct WorkspaceMember {
+    account_id: AccountIdWrapper,
+    role: Role,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+pub struct WorkspaceMembership {
+    workspace_id: AccountIdWrapper,
+    role: Role,
+}
+
+/// Whether the ID has the `<name>.<contract>` shape of a workspace. Those
+/// are only registered through `create_workspace`.
+pub(crate) fn is_workspace_id(account_id: &AccountId) -> bool {
+    account_id
+        .as_str()
+        .strip_suffix(env::current_account_id().as_str())
+        .and_then(|prefix| prefix.strip_suffix('.'))
+        .is_some_and(|name| !name.is_empty() && !name.contains('.'))
+}
+
+impl Task {
+    /// The account credited with the task's points.
+    pub(crate) fn points_recipient(&self) -> AccountId {
+        self.assignee.as_ref().unwrap_or(&self.owner).0.clone()
+    }
+}
+
+#[near_bindgen]
+impl TodoListV1 {
+    /// Creates the workspace `<name>.<contract>` with the caller as its owner
+    /// and returns its ID. Only this contract could create that account, so the
+    /// ID belongs to nobody else. The workspace holds its own tasks, rewards and
+    /// schedule, passed as `account_id` to the usual methods, and the attached
+    /// deposit becomes its storage balance.
+    #[payable]
+    #[handle_result]
+    pub fn create_workspace(&mut self, name: String) -> Result<AccountId, ContractError> {
+        let owner_id = env::predecessor_account_id();
+        let workspace_id: AccountId = if name.is_empty() || name.contains('.') {
+            None
+        } else {
+            format!("{}.{}", name, env::current_account_id()).parse().ok()
+        }
+        .ok_or(ContractError::InvalidWorkspaceName(name))?;
+        if self.storage_accounts.contains_key(&workspace_id) {
+            return Err(ContractError::WorkspaceExists(workspace_id));
+        }
+        let deposit = env::attached_deposit();
+        let required = min_storage_balance();
+        if deposit < required {
+            return Err(ContractError::InsufficientStorageBalance { required, available: deposit });
+        }
+
+        self.register_account(workspace_id.clone(), deposit);
+        let initial_storage_usage = env::storage_usage();
+        TodoEvent::WorkspaceCreated { account_id: &workspace_id, owner_id: &owner_id }.emit();
+        self.workspaces.insert(
+            workspace_id.clone(),
+            UnorderedMap::new(StorageKey::WorkspaceMembers { account_hash: account_hash(&workspace_id) }),
+        );
+        self.put_member(&workspace_id, owner_id, Role::Owner);
+        self.settle_storage(&workspace_id, initial_storage_usage)?;
+        Ok(workspace_id)
+    }
+
+    /// Adds `member_id` to the workspace or changes their role. Owners only.
+    #[handle_result]
+    pub fn set_member_role(&mut self, workspace_id: AccountId, member_id: AccountId, role: Role) -> Result<(), ContractError> {
+        self.authorize_workspace_owner(&workspace_id)?;
+        let initial_storage_usage = self.begin_storage_tracking(&workspace_id)?;
+        if role != Role::Owner {
+            self.ensure_other_owner(&workspace_id, &member_id)?;
+        }
+
+        TodoEvent::MemberUpdated { account_id: &workspace_id, member_id: &member_id, role }.emit();
+        self.put_member(&workspace_id, member_id, role);
+        self.settle_storage(&workspace_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Removes `member_id` from the workspace. Owners can remove anyone and
+    /// members can remove themselves. Tasks assigned to them stay assigned.
+    #[handle_result]
+    pub fn remove_member(&mut self, workspace_id: AccountId, member_id: AccountId) -> Result<(), ContractError> {
+        if member_id != env::predecessor_account_id() {
+            self.authorize_workspace_owner(&workspace_id)?;
+        }
+        let initial_storage_usage = self.begin_storage_tracking(&workspace_id)?;
+        self.ensure_other_owner(&workspace_id, &member_id)?;
+
+        self.workspaces
+            .get_mut(&workspace_id)
+            .and_then(|members| members.remove(&member_id))
+            .ok_or_else(|| ContractError::NotAMember(member_id.clone()))?;
+        self.drop_membership(&member_id, &workspace_id);
+        TodoEvent::MemberRemoved { account_id: &workspace_id, member_id: &member_id }.emit();
+        self.settle_storage(&workspace_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Makes `assignee` the one credited for completing the task, or the list
+    /// itself when `None`. In a workspace the assignee must be a member; in a
+    /// personal list it can only be its owner.
+    #[handle_result]
+    pub fn assign_task(&mut self, id: u64, assignee: Option<AccountId>, account_id: Option<AccountId>) -> Result<(), ContractError> {
+        let account_id = self.authorize(account_id, Permission::ManageTasks)?;
+        let initial_storage_usage = self.begin_storage_tracking(&account_id)?;
+        if let Some(assignee) = &assignee {
+            if *assignee != account_id && self.workspace_role(&account_id, assignee).is_none() {
+                return Err(ContractError::NotAMember(assignee.clone()));
+            }
+        }
+
+        let task = self.task_mut(&account_id, id)?;
+        // Completed tasks keep the assignee their points were credited to.
+        if task.completed {
+            return Err(ContractError::AlreadyCompleted(id));
+        }
+        task.assignee = assignee.clone().map(AccountIdWrapper);
+        TodoEvent::TaskAssigned { account_id: &account_id, task_id: id, assignee: assignee.as_ref() }.emit();
+        self.settle_storage(&account_id, initial_storage_usage)?;
+        Ok(())
+    }
+
+    /// Sends `amount`, or all of the workspace's available storage balance,
+    /// to the calling owner.
+    #[payable]
+    #[handle_result]
+    pub fn workspace_storage_withdraw(&mut self, workspace_id: AccountId, amount: Option<U128>) -> Result<StorageBalance, ContractError> {
+        assert_one_yocto();
+        self.authorize_workspace_owner(&workspace_id)?;
+        self.withdraw_storage(&workspace_id, amount, env::predecessor_account_id())
+    }
+
+    /// Deletes the workspace with all its tasks, rewards and points, and sends
+    /// its whole storage deposit to the calling owner. Owners only.
+    #[payable]
+    #[handle_result]
+    pub fn delete_workspace(&mut self, workspace_id: AccountId) -> Result<(), ContractError> {
+        assert_one_yocto();
+        self.authorize_workspace_owner(&workspace_id)?;
+        let owner_id = env::predecessor_account_id();
+        TodoEvent::WorkspaceDeleted { account_id: &workspace_id, owner_id: &owner_id }.emit();
+        self.unregister_account(&workspace_id, owner_id);
+        Ok(())
+    }
+
+    pub fn get_workspace_members(&self, workspace_id: AccountId) -> Vec<WorkspaceMember> {
+        self.workspaces
+            .get(&workspace_id)
+            .map(|members| {
+                members
+                    .iter()
+                    .map(|(account_id, role)| WorkspaceMember { account_id: AccountIdWrapper(account_id.clone()), role: *role })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_workspaces(&self, account_id: AccountId) -> Vec<WorkspaceMembership> {
+        self.memberships
+            .get(&account_id)
+            .map(|workspace_ids| {
+                workspace_ids
+                    .iter()
+                    .filter_map(|workspace_id| {
+                        let role = self.workspace_role(workspace_id, &account_id)?;
+                        Some(WorkspaceMembership { workspace_id: AccountIdWrapper(workspace_id.clone()), role })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl TodoListV1 {
+    pub(crate) fn workspace_role(&self, workspace_id: &AccountId, member_id: &AccountId) -> Option<Role> {
+        self.workspaces.get(workspace_id).and_then(|members| members.get(member_id)).copied()
+    }
+
+    /// Whether `member_id` has a role in `workspace_id` that allows `permission`.
+    pub(crate) fn has_workspace_permission(&self, workspace_id: &AccountId, member_id: &AccountId, permission: Permission) -> bool {
+        self.workspace_role(workspace_id, member_id).is_some_and(|role| role.allows(permission))
+    }
+
+    fn authorize_workspace_owner(&self, workspace_id: &AccountId) -> Result<(), ContractError> {
+        match self.workspace_role(workspace_id, &env::predecessor_account_id()) {
+            Some(Role::Owner) => Ok(()),
+            _ => Err(ContractError::Unauthorized),
+        }
+    }
+
+    /// Fails if `member_id` is the workspace's only owner.
+    pub(crate) fn ensure_other_owner(&self, workspace_id: &AccountId, member_id: &AccountId) -> Result<(), ContractError> {
+        let Some(members) = self.workspaces.get(workspace_id) else {
+            return Ok(());
+        };
+        if members.get(member_id) == Some(&Role::Owner)
+            && !members.iter().any(|(account_id, role)| *role == Role::Owner && account_id != member_id)
+        {
+            return Err(ContractError::LastWorkspaceOwner);
+        }
+        Ok(())
+    }
+
+    /// Drops the workspace from the member's list of memberships.
+    pub(crate) fn drop_membership(&mut self, member_id: &AccountId, workspace_id: &AccountId) {
+        if let Some(workspace_ids) = self.memberships.get_mut(member_id) {
+            workspace_ids.retain(|id| id != workspace_id);
+            if workspace_ids.is_empty() {
+                self.memberships.remove(member_id);
+            }
+        }
+    }
+
+    fn put_member(&mut self, workspace_id: &AccountId, member_id: AccountId, role: Role) {
+        let workspace_ids = self.memberships.entry(member_id.clone()).or_default();
+        if !workspace_ids.contains(workspace_id) {
+            workspace_ids.push(workspace_id.clone());
+        }
+        if let Some(members) = self.workspaces.get_mut(workspace_id) {
+            members.insert(member_id, role);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{get_context, setup_contract};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    fn create_workspace(contract: &mut TodoListV1, name: &str) -> AccountId {
+        testing_env!(get_context(accounts(1)).attached_deposit(ONE_NEAR).build());
+        let workspace_id = contract.create_workspace(name.to_string()).unwrap();
+        testing_env!(get_context(accounts(1)).build());
+        workspace_id
+    }
+
+    #[test]
+    fn test_workspace_roles() {
+        let mut contract = setup_contract(accounts(1));
+        let team = create_workspace(&mut contract, "team");
+        assert_eq!(team, "team.alice".parse::<AccountId>().unwrap());
+        contract.set_member_role(team.clone(), accounts(2), Role::Editor).unwrap();
+        contract.set_member_role(team.clone(), accounts(3), Role::Viewer).unwrap();
+        assert_eq!(contract.get_workspace_members(team.clone()).len(), 3);
+        assert_eq!(contract.get_workspaces(accounts(2)), vec![WorkspaceMembership { workspace_id: AccountIdWrapper(team.clone()), role: Role::Editor }]);
+
+        testing_env!(get_context(accounts(2)).build());
+        let task = contract.add_task("Shared".to_string(), "".to_string(), 1, None, None, 10, Some(team.clone())).unwrap();
+        assert_eq!(contract.add_reward("Pizza".to_string(), "".to_string(), 10, Some(team.clone())), Err(ContractError::Unauthorized));
+        assert_eq!(contract.set_member_role(team.clone(), accounts(2), Role::Owner), Err(ContractError::Unauthorized));
+
+        testing_env!(get_context(accounts(3)).build());
+        assert_eq!(contract.mark_complete(task, Some(team.clone())), Err(ContractError::Unauthorized));
+        contract.remove_member(team.clone(), accounts(3)).unwrap();
+        assert!(contract.get_workspaces(accounts(3)).is_empty());
+
+        testing_env!(get_context(accounts(1)).build());
+        assert_eq!(contract.remove_member(team.clone(), accounts(1)), Err(ContractError::LastWorkspaceOwner));
+        assert_eq!(contract.set_member_role(team.clone(), accounts(1), Role::Editor), Err(ContractError::LastWorkspaceOwner));
+        assert_eq!(
+            contract.create_workspace("my.team".to_string()),
+            Err(ContractError::InvalidWorkspaceName("my.team".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_completion_credits_assignee() {
+        let mut contract = setup_contract(accounts(1));
+        let team = create_workspace(&mut contract, "team");
+        contract.set_member_role(team.clone(), accounts(2), Role::Editor).unwrap();
+        let assigned = contract.add_task("Assigned".to_string(), "".to_string(), 1, None, None, 10, Some(team.clone())).unwrap();
+        let unassigned = contract.add_task("Unassigned".to_string(), "".to_string(), 1, None, None, 5, Some(team.clone())).unwrap();
+        contract.assign_task(assigned, Some(accounts(2)), Some(team.clone())).unwrap();
+        assert_eq!(contract.assign_task(unassigned, Some(accounts(3)), Some(team.clone())), Err(ContractError::NotAMember(accounts(3))));
+
+        contract.mark_complete(assigned, Some(team.clone())).unwrap();
+        contract.mark_complete(unassigned, Some(team.clone())).unwrap();
+        assert_eq!(contract.get_account_reward_points(accounts(2)), 10);
+        assert_eq!(contract.get_account_reward_points(accounts(1)), 0);
+        assert_eq!(contract.get_account_reward_points(team.clone()), 5);
+
+        contract.mark_incomplete(assigned, Some(team.clone())).unwrap();
+        assert_eq!(contract.get_account_reward_points(accounts(2)), 0);
+    }
+
+    #[test]
+    fn test_workspace_badges_go_to_members() {
+        let mut contract = setup_contract(accounts(1));
+        let team = create_workspace(&mut contract, "team");
+        contract.set_member_role(team.clone(), accounts(2), Role::Editor).unwrap();
+        for i in 0..10 {
+            let task = contract.add_task(format!("Task {}", i), "".to_string(), 1, None, None, 1, Some(team.clone())).unwrap();
+            contract.assign_task(task, Some(accounts(2)), Some(team.clone())).unwrap();
+            contract.mark_complete(task, Some(team.clone())).unwrap();
+        }
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(1));
+        assert_eq!(contract.nft_supply_for_owner(team.clone()), U128(0));
+
+        let task = contract.add_task("Shared".to_string(), "".to_string(), 1, None, None, 20, Some(team.clone())).unwrap();
+        let reward = contract.add_reward("Lunch".to_string(), "".to_string(), 20, Some(team.clone())).unwrap();
+        contract.mark_complete(task, Some(team.clone())).unwrap();
+        let token_id = contract.redeem_reward(reward, Some(team.clone())).unwrap();
+        assert_eq!(contract.nft_tokens_for_owner(accounts(1), None, None), vec![contract.nft_token(token_id).unwrap()]);
+        assert_eq!(contract.nft_supply_for_owner(team), U128(0));
+    }
+
+    #[test]
+    fn test_withdraw_and_delete_workspace() {
+        let mut contract = setup_contract(accounts(1));
+        let team = create_workspace(&mut contract, "team");
+        assert!(is_workspace_id(&team));
+        assert!(!is_workspace_id(&accounts(1)));
+        contract.set_member_role(team.clone(), accounts(2), Role::Editor).unwrap();
+        contract.add_task("Shared".to_string(), "".to_string(), 1, None, None, 10, Some(team.clone())).unwrap();
+
+        testing_env!(get_context(accounts(2)).attached_deposit(1).build());
+        assert_eq!(contract.workspace_storage_withdraw(team.clone(), None), Err(ContractError::Unauthorized));
+        assert_eq!(contract.delete_workspace(team.clone()), Err(ContractError::Unauthorized));
+
+        testing_env!(get_context(accounts(1)).attached_deposit(1).build());
+        let balance = contract.workspace_storage_withdraw(team.clone(), None).unwrap();
+        assert_eq!(balance.available, U128(0));
+        contract.delete_workspace(team.clone()).unwrap();
+        assert_eq!(contract.storage_balance_of(team.clone()), None);
+        assert!(contract.get_workspaces(accounts(2)).is_empty());
+        assert!(contract.get_tasks(team.clone()).is_empty());
+        assert_eq!(create_workspace(&mut contract, "team"), team);
+    }
+}